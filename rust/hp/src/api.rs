@@ -1,13 +1,105 @@
-use anyhow::{bail, Result};
+use crate::auth::Auth;
+use anyhow::{bail, Context, Result};
 use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use reqwest::{Method, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json;
+use std::time::Duration;
+
+/// Default number of suggestions `get_suggestions` asks for when the request's own `limit` is not
+/// positive, unless an `ApiClientBuilder::limit` overrides it. Other endpoints do not use it.
+const DEFAULT_LIMIT: i32 = 20;
+
+/// Default retry budget for idempotent `GET` requests that hit a 5xx or connection error.
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// A pre-execution hook run against the command about to be executed. Returning `Err` aborts
+/// the execution before the record is submitted.
+pub type PreExecuteHook = Box<dyn Fn(&Command) -> Result<()> + Send + Sync>;
+
+/// A post-execution hook run after the execution history record has been submitted, receiving
+/// the executed command and the record that was sent.
+pub type PostExecuteHook = Box<dyn Fn(&Command, &ExecutionHistoryCreate) -> Result<()> + Send + Sync>;
 
 /// The main client for making API calls.
 pub struct ApiClient {
+    http: reqwest::Client,
     base_url: String,
+    auth: Auth,
+    default_user: Option<String>,
+    default_hostname: Option<String>,
+    default_cwd: Option<String>,
+    limit: i32,
+    max_retries: u32,
+    pre_execute_hooks: Vec<PreExecuteHook>,
+    post_execute_hooks: Vec<PostExecuteHook>,
+}
+
+/// Transport-security configuration for talking to a self-hosted hiproc server behind an
+/// internal PKI: a custom CA bundle to trust self-signed server certs, and/or a client
+/// certificate + private key for mutual TLS. Backed by rustls via `reqwest`'s `rustls-tls`
+/// feature.
+#[derive(Default, Clone)]
+pub struct TlsConfig {
+    ca_cert_pem: Option<Vec<u8>>,
+    client_identity_pem: Option<Vec<u8>>,
+    accept_invalid_hostnames: bool,
+}
+
+impl TlsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trusts the given PEM-encoded CA certificate bundle in addition to the system roots, for
+    /// validating a self-signed or internally-issued server certificate.
+    pub fn with_ca_cert_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.ca_cert_pem = Some(pem.into());
+        self
+    }
+
+    /// Presents the given PEM-encoded client certificate + private key for mutual TLS.
+    pub fn with_client_identity_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.client_identity_pem = Some(pem.into());
+        self
+    }
+
+    /// Skips server hostname verification. Only useful against test servers whose certificate
+    /// doesn't cover the hostname being dialed; never enable this for a production endpoint.
+    pub fn accept_invalid_hostnames(mut self, accept: bool) -> Self {
+        self.accept_invalid_hostnames = accept;
+        self
+    }
 }
 
+/// Exponential backoff delay for the given (1-indexed) retry attempt: 100ms, 200ms, 400ms, ...
+fn retry_backoff(attempt: u32) -> Duration {
+    Duration::from_millis(100 * 2u64.saturating_pow(attempt.saturating_sub(1)))
+}
+
+/// A structured error a caller can match on via `anyhow::Error::downcast_ref`, for the cases
+/// where distinguishing the failure kind matters more than just displaying a message.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The server rejected the request's credentials (HTTP 401 or 403).
+    AuthenticationFailed(StatusCode),
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::AuthenticationFailed(status) => write!(
+                f,
+                "Authentication failed ({}): check the configured credentials",
+                status
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
 #[derive(Serialize)]
 struct RecallRequest<'a> {
     name: &'a str,
@@ -18,25 +110,113 @@ struct RecallRequest<'a> {
 }
 
 impl ApiClient {
-    /// Creates a new `ApiClient`.
+    /// Creates a new `ApiClient` with no authentication, building the shared `reqwest::Client`
+    /// once so every request reuses the same connection pool and TLS config instead of paying
+    /// that setup cost per call.
     pub fn new(base_url: String) -> Self {
-        Self { base_url }
+        Self::with_auth(base_url, Auth::None)
+    }
+
+    /// Creates a new `ApiClient` that applies `auth` to every outgoing request.
+    pub fn with_auth(base_url: String, auth: Auth) -> Self {
+        ApiClientBuilder::new(base_url)
+            .auth(auth)
+            .build()
+            .expect("building an ApiClient with no timeout cannot fail")
+    }
+
+    /// Starts a [`ApiClientBuilder`] for configuring timeouts, retries, default context, and
+    /// auth before constructing an `ApiClient`.
+    pub fn builder(base_url: impl Into<String>) -> ApiClientBuilder {
+        ApiClientBuilder::new(base_url)
+    }
+
+    /// Creates a new `ApiClient` against a server secured with a custom CA and/or mutual TLS.
+    pub fn with_tls(base_url: String, tls: TlsConfig) -> Result<Self> {
+        ApiClientBuilder::new(base_url).tls(tls).build()
+    }
+
+    /// Builds a request against `{base_url}{path}`, attaching query params, a JSON body, and the
+    /// configured `Auth`. Every public method funnels through here so connection reuse, headers,
+    /// and auth policy live in one place instead of being repeated per call.
+    async fn build_request<J: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        query: Option<&[(&str, &str)]>,
+        json: Option<&J>,
+    ) -> Result<reqwest::RequestBuilder> {
+        let mut request = self
+            .http
+            .request(method.clone(), format!("{}{}", self.base_url, path));
+
+        if let Some(query) = query {
+            request = request.query(query);
+        }
+        if let Some(json) = json {
+            request = request.json(json);
+        }
+
+        self.auth.apply(&self.http, &method, path, request).await
+    }
+
+    /// Sends `request` and maps a 401/403 response to a typed [`ApiError::AuthenticationFailed`]
+    /// instead of letting it surface later as an opaque deserialization failure. `GET` requests
+    /// are idempotent, so a 5xx response or a connection-level error is retried with exponential
+    /// backoff (up to `max_retries` times) before giving up.
+    async fn execute(
+        &self,
+        method: Method,
+        mut request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let retry_request = if method == Method::GET && attempt < self.max_retries {
+                request.try_clone()
+            } else {
+                None
+            };
+
+            match request.send().await {
+                Ok(res) if method == Method::GET && res.status().is_server_error() => {
+                    match retry_request {
+                        Some(next) => {
+                            request = next;
+                            attempt += 1;
+                            tokio::time::sleep(retry_backoff(attempt)).await;
+                            continue;
+                        }
+                        None => return Ok(res),
+                    }
+                }
+                Ok(res) => {
+                    if res.status() == StatusCode::UNAUTHORIZED || res.status() == StatusCode::FORBIDDEN {
+                        return Err(ApiError::AuthenticationFailed(res.status()).into());
+                    }
+                    return Ok(res);
+                }
+                Err(err) => match retry_request {
+                    Some(next) => {
+                        request = next;
+                        attempt += 1;
+                        tokio::time::sleep(retry_backoff(attempt)).await;
+                    }
+                    None => return Err(err.into()),
+                },
+            }
+        }
     }
 
     /// Saves a new command to the server.
     pub async fn save_command(&self, new_command: NewCommand) -> Result<Command> {
-        let client = reqwest::Client::new();
-        let res = client
-            .post(format!("{}/commands/", self.base_url))
-            .json(&new_command)
-            .send()
+        let res = self
+            .execute(Method::POST, self.build_request(Method::POST, "/commands/", None, Some(&new_command)).await?)
             .await?;
         Ok(res.error_for_status()?.json().await?)
     }
 
     /// Searches for commands on the server.
     pub async fn get_commands(&self, query: &str, namespace: Option<&str>, user: Option<&str>, scope: Option<&str>) -> Result<Vec<Command>> {
-        let client = reqwest::Client::new();
         let mut query_params = vec![("q", query)];
         if let Some(ns) = namespace {
             query_params.push(("namespace", ns));
@@ -48,17 +228,29 @@ impl ApiClient {
             query_params.push(("scope", s));
         }
 
-        let res = client
-            .get(format!("{}/commands/", self.base_url))
-            .query(&query_params)
-            .send()
+        let res = self
+            .execute(Method::GET, self.build_request::<()>(Method::GET, "/commands/", Some(&query_params), None).await?)
+            .await?;
+        Ok(res.error_for_status()?.json().await?)
+    }
+
+    /// Full-text search across every saved command's `command_string`, `namespace`, `name`, and
+    /// `description`, across the whole corpus (not scoped to the current user). Backs
+    /// `hp help --find`; unlike [`ApiClient::get_commands`] this has no namespace/user/scope
+    /// filters, since it's a "what did I have that did X" lookup rather than a targeted search.
+    pub async fn find_commands_by_text(&self, query: &str) -> Result<Vec<Command>> {
+        let res = self
+            .execute(
+                Method::GET,
+                self.build_request::<()>(Method::GET, "/commands/find", Some(&[("q", query)]), None)
+                    .await?,
+            )
             .await?;
         Ok(res.error_for_status()?.json().await?)
     }
 
     /// Recalls a command from the server.
     pub async fn recall_command(&self, namespace: &str, name: &str, user: &str, hostname: &str, cwd: &str) -> Result<Command> {
-        let client = reqwest::Client::new();
         let recall_request = RecallRequest {
             name,
             namespace,
@@ -66,10 +258,8 @@ impl ApiClient {
             hostname,
             cwd,
         };
-        let res = client
-            .post(format!("{}/commands/recall", self.base_url))
-            .json(&recall_request)
-            .send()
+        let res = self
+            .execute(Method::POST, self.build_request(Method::POST, "/commands/recall", None, Some(&recall_request)).await?)
             .await?;
 
         if res.status() == 404 {
@@ -81,37 +271,37 @@ impl ApiClient {
 
     /// Gets a list of all namespaces from the server.
     pub async fn get_namespaces(&self) -> Result<Vec<String>> {
-        let client = reqwest::Client::new();
-        let res = client
-            .get(format!("{}/namespaces/", self.base_url))
-            .send()
+        let res = self
+            .execute(Method::GET, self.build_request::<()>(Method::GET, "/namespaces/", None, None).await?)
             .await?;
         Ok(res.error_for_status()?.json().await?)
     }
 
     /// Deletes a command from the server.
     pub async fn delete_command(&self, command_id: i32, user: &str) -> Result<Command> {
-        let client = reqwest::Client::new();
-        let res = client
-            .delete(format!("{}/commands/{}", self.base_url, command_id))
-            .query(&[("user", user)])
-            .send()
+        let res = self
+            .execute(Method::DELETE, self.build_request::<()>(Method::DELETE,
+                &format!("/commands/{}", command_id),
+                Some(&[("user", user)]),
+                None,
+            ).await?)
             .await?;
-        
+
         if res.status() == 404 {
             bail!("Command not found, or you don't have permission to delete it.");
         }
 
         Ok(res.error_for_status()?.json().await?)
     }
+
     /// Updates a command on the server.
     pub async fn update_command(&self, command_id: i32, user: &str, command_update: CommandUpdate) -> Result<Command> {
-        let client = reqwest::Client::new();
-        let res = client
-            .put(format!("{}/commands/{}", self.base_url, command_id))
-            .query(&[("user", user)])
-            .json(&command_update)
-            .send()
+        let res = self
+            .execute(Method::PUT, self.build_request(Method::PUT,
+                &format!("/commands/{}", command_id),
+                Some(&[("user", user)]),
+                Some(&command_update),
+            ).await?)
             .await?;
 
         if res.status() == 404 {
@@ -121,25 +311,63 @@ impl ApiClient {
         Ok(res.error_for_status()?.json().await?)
     }
 
-    /// Gets all commands for a user.
+    /// Page size [`ApiClient::get_all_user_commands`] walks with internally, so a user with a
+    /// very large command library doesn't force the server to serialize (and `hp` to buffer) an
+    /// unbounded response in one request.
+    const ALL_USER_COMMANDS_PAGE_SIZE: i64 = 200;
+
+    /// Gets all commands for a user, transparently paginating via [`ApiClient::stream_all_user_commands`]
+    /// rather than requesting everything in one unbounded response.
     pub async fn get_all_user_commands(&self, user: &str) -> Result<Vec<Command>> {
-        let client = reqwest::Client::new();
-        let res = client
-            .get(format!("{}/commands/all", self.base_url))
-            .query(&[("user", user)])
-            .send()
+        self.stream_all_user_commands(user, Self::ALL_USER_COMMANDS_PAGE_SIZE)
+            .try_collect()
+            .await
+    }
+
+    /// Gets one page of a user's commands. `page` is 1-indexed; `next_cursor` on the returned
+    /// [`Page`] is `Some` when the page came back full, signalling there may be more to fetch.
+    pub async fn get_all_user_commands_page(&self, user: &str, page: i64, per_page: i64) -> Result<Page<Command>> {
+        let page_str = page.to_string();
+        let per_page_str = per_page.to_string();
+        let res = self
+            .execute(Method::GET, self.build_request::<()>(
+                Method::GET,
+                "/commands/all",
+                Some(&[("user", user), ("page", &page_str), ("per_page", &per_page_str)]),
+                None,
+            ).await?)
             .await?;
-        Ok(res.error_for_status()?.json().await?)
+        let items: Vec<Command> = res.error_for_status()?.json().await?;
+        Ok(Page::from_page_and_items(page, per_page, items))
+    }
+
+    /// Streams every command for `user` across as many pages as needed, transparently walking
+    /// `next_cursor` so callers don't have to manage offsets themselves.
+    pub fn stream_all_user_commands<'a>(
+        &'a self,
+        user: &'a str,
+        per_page: i64,
+    ) -> impl Stream<Item = Result<Command>> + 'a {
+        stream::try_unfold(Some(1i64), move |cursor| async move {
+            let Some(page) = cursor else {
+                return Ok(None);
+            };
+            let result = self.get_all_user_commands_page(user, page, per_page).await?;
+            let next = result.next_cursor.as_ref().map(|_| page + 1);
+            Ok(Some((result.items, next)))
+        })
+        .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+        .try_flatten()
     }
 
-/// Renames a command on the server.
+    /// Renames a command on the server.
     pub async fn rename_command(&self, command_id: i32, user: &str, command_rename: CommandRename) -> Result<Command> {
-        let client = reqwest::Client::new();
-        let res = client
-            .patch(format!("{}/commands/{}", self.base_url, command_id))
-            .query(&[("user", user)])
-            .json(&command_rename)
-            .send()
+        let res = self
+            .execute(Method::PATCH, self.build_request(Method::PATCH,
+                &format!("/commands/{}", command_id),
+                Some(&[("user", user)]),
+                Some(&command_rename),
+            ).await?)
             .await?;
 
         if res.status() == 404 {
@@ -151,11 +379,12 @@ impl ApiClient {
 
     /// Gets a single command by ID.
     pub async fn get_command_by_id(&self, command_id: i32, user: &str) -> Result<Command> {
-        let client = reqwest::Client::new();
-        let res = client
-            .get(format!("{}/commands/by-id/{}", self.base_url, command_id))
-            .query(&[("user", user)])
-            .send()
+        let res = self
+            .execute(Method::GET, self.build_request::<()>(Method::GET,
+                &format!("/commands/by-id/{}", command_id),
+                Some(&[("user", user)]),
+                None,
+            ).await?)
             .await?;
 
         if res.status() == 404 {
@@ -167,11 +396,12 @@ impl ApiClient {
 
     /// Executes a command by ID, tracking the execution.
     pub async fn execute_command(&self, command_id: i32, user: &str) -> Result<Command> {
-        let client = reqwest::Client::new();
-        let res = client
-            .post(format!("{}/commands/{}/execute", self.base_url, command_id))
-            .query(&[("user", user)])
-            .send()
+        let res = self
+            .execute(Method::POST, self.build_request::<()>(Method::POST,
+                &format!("/commands/{}/execute", command_id),
+                Some(&[("user", user)]),
+                None,
+            ).await?)
             .await?;
 
         if res.status() == 404 {
@@ -181,13 +411,15 @@ impl ApiClient {
         Ok(res.error_for_status()?.json().await?)
     }
 
-    /// Recalls a command by name with enhanced contextual matching.
-    pub async fn recall_command_by_name(&self, request: RecallByNameRequest) -> Result<Command> {
-        let client = reqwest::Client::new();
-        let res = client
-            .post(format!("{}/commands/recall-by-name", self.base_url))
-            .json(&request)
-            .send()
+    /// Recalls a command by name with enhanced contextual matching. Any of `user`, `hostname`,
+    /// or `cwd` left unset on `request` falls back to this client's builder-configured defaults.
+    pub async fn recall_command_by_name(&self, mut request: RecallByNameRequest) -> Result<Command> {
+        request.user = request.user.or_else(|| self.default_user.clone());
+        request.hostname = request.hostname.or_else(|| self.default_hostname.clone());
+        request.cwd = request.cwd.or_else(|| self.default_cwd.clone());
+
+        let res = self
+            .execute(Method::POST, self.build_request(Method::POST, "/commands/recall-by-name", None, Some(&request)).await?)
             .await?;
 
         if res.status() == 404 {
@@ -197,13 +429,19 @@ impl ApiClient {
         Ok(res.error_for_status()?.json().await?)
     }
 
-    /// Get contextual command suggestions.
-    pub async fn get_suggestions(&self, request: SuggestionsRequest) -> Result<Vec<Command>> {
-        let client = reqwest::Client::new();
-        let res = client
-            .post(format!("{}/suggestions", self.base_url))
-            .json(&request)
-            .send()
+    /// Get contextual command suggestions. Any of `user`, `hostname`, or `cwd` left unset on
+    /// `request` falls back to this client's builder-configured defaults, and `limit <= 0` falls
+    /// back to the builder-configured result limit.
+    pub async fn get_suggestions(&self, mut request: SuggestionsRequest) -> Result<Vec<Command>> {
+        request.user = request.user.or_else(|| self.default_user.clone());
+        request.hostname = request.hostname.or_else(|| self.default_hostname.clone());
+        request.cwd = request.cwd.or_else(|| self.default_cwd.clone());
+        if request.limit <= 0 {
+            request.limit = self.limit;
+        }
+
+        let res = self
+            .execute(Method::POST, self.build_request(Method::POST, "/suggestions", None, Some(&request)).await?)
             .await?;
 
         Ok(res.error_for_status()?.json().await?)
@@ -211,11 +449,8 @@ impl ApiClient {
 
     /// Detect project context and get namespace suggestions.
     pub async fn detect_project_context(&self, request: ProjectContextRequest) -> Result<ProjectContextResponse> {
-        let client = reqwest::Client::new();
-        let res = client
-            .post(format!("{}/project-context", self.base_url))
-            .json(&request)
-            .send()
+        let res = self
+            .execute(Method::POST, self.build_request(Method::POST, "/project-context", None, Some(&request)).await?)
             .await?;
 
         Ok(res.error_for_status()?.json().await?)
@@ -223,36 +458,73 @@ impl ApiClient {
 
     /// Get commands similar to the specified command.
     pub async fn get_similar_commands(&self, command_id: i32, limit: Option<i32>) -> Result<Vec<Command>> {
-        let client = reqwest::Client::new();
-        let mut url = format!("{}/commands/{}/similar", self.base_url, command_id);
-        
-        if let Some(limit) = limit {
-            url = format!("{}?limit={}", url, limit);
-        }
-        
-        let res = client.get(url).send().await?;
+        let limit_str = limit.map(|l| l.to_string());
+        let query = limit_str.as_deref().map(|l| vec![("limit", l)]);
+
+        let res = self
+            .execute(Method::GET, self.build_request::<()>(Method::GET,
+                &format!("/commands/{}/similar", command_id),
+                query.as_deref(),
+                None,
+            ).await?)
+            .await?;
+        Ok(res.error_for_status()?.json().await?)
+    }
+
+    /// Gets the most recent execution history records for a single command, newest first.
+    pub async fn get_execution_history(&self, command_id: i32, limit: i32) -> Result<Vec<ExecutionRecord>> {
+        let limit_str = limit.to_string();
+        let res = self
+            .execute(Method::GET, self.build_request::<()>(Method::GET,
+                &format!("/commands/{}/history", command_id),
+                Some(&[("limit", limit_str.as_str())]),
+                None,
+            ).await?)
+            .await?;
         Ok(res.error_for_status()?.json().await?)
     }
 
     /// Create an execution history record for analytics.
     pub async fn create_execution_record(&self, execution: ExecutionHistoryCreate) -> Result<serde_json::Value> {
-        let client = reqwest::Client::new();
-        let res = client
-            .post(format!("{}/execution-history", self.base_url))
-            .json(&execution)
-            .send()
+        let res = self
+            .execute(Method::POST, self.build_request(Method::POST, "/execution-history", None, Some(&execution)).await?)
             .await?;
 
         Ok(res.error_for_status()?.json().await?)
     }
 
+    /// Runs registered pre-execute hooks against `command` in registration order. The first
+    /// error returned by a hook stops the remaining hooks and is returned. Callers run this after
+    /// `command` was fetched through `execute_command`, so a failing hook keeps the command from
+    /// running locally but does not undo that request to the server.
+    pub fn run_pre_execute_hooks(&self, command: &Command) -> Result<()> {
+        for hook in &self.pre_execute_hooks {
+            hook(command)?;
+        }
+        Ok(())
+    }
+
+    /// Submits `execution` as an execution-history record for `command`, then fires registered
+    /// post-execute hooks with the command and the submitted record. This turns execution
+    /// tracking into a real pipeline (pre-hooks -> record -> post-hooks) instead of a single
+    /// unconditional `create_execution_record` call.
+    pub async fn record_execution(
+        &self,
+        command: &Command,
+        execution: ExecutionHistoryCreate,
+    ) -> Result<serde_json::Value> {
+        let response = self.create_execution_record(execution.clone()).await?;
+        for hook in &self.post_execute_hooks {
+            hook(command, &execution)?;
+        }
+        Ok(response)
+    }
+
     /// Get execution analytics.
     pub async fn get_execution_analytics(&self, user: Option<&str>, days: Option<i32>) -> Result<serde_json::Value> {
-        let client = reqwest::Client::new();
-        let url = format!("{}/analytics/execution", self.base_url);
         let mut params = Vec::new();
         let days_str;
-        
+
         if let Some(user) = user {
             params.push(("user", user));
         }
@@ -260,12 +532,179 @@ impl ApiClient {
             days_str = days.to_string();
             params.push(("days", days_str.as_str()));
         }
-        
-        let res = client.get(url).query(&params).send().await?;
+
+        let res = self
+            .execute(Method::GET, self.build_request::<()>(Method::GET, "/analytics/execution", Some(&params), None).await?)
+            .await?;
         Ok(res.error_for_status()?.json().await?)
     }
 }
 
+/// Builds an [`ApiClient`] with a request timeout, retry budget, default caller context, and
+/// auth scheme, so callers don't have to thread `user`/`hostname`/`cwd` through every request.
+pub struct ApiClientBuilder {
+    base_url: String,
+    timeout: Option<Duration>,
+    auth: Auth,
+    tls: Option<TlsConfig>,
+    default_user: Option<String>,
+    default_hostname: Option<String>,
+    default_cwd: Option<String>,
+    limit: i32,
+    max_retries: u32,
+    pre_execute_hooks: Vec<PreExecuteHook>,
+    post_execute_hooks: Vec<PostExecuteHook>,
+}
+
+impl ApiClientBuilder {
+    /// Starts a builder targeting `base_url`, with no timeout, no auth, no TLS customization,
+    /// and the default retry budget and result limit.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            timeout: None,
+            auth: Auth::None,
+            tls: None,
+            default_user: None,
+            default_hostname: None,
+            default_cwd: None,
+            limit: DEFAULT_LIMIT,
+            max_retries: DEFAULT_MAX_RETRIES,
+            pre_execute_hooks: Vec::new(),
+            post_execute_hooks: Vec::new(),
+        }
+    }
+
+    /// Sets the TLS configuration (custom CA, client identity, hostname verification) used to
+    /// connect to `base_url`.
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Registers a pre-execute hook, run in registration order before an execution is recorded.
+    pub fn on_pre_execute(mut self, hook: impl Fn(&Command) -> Result<()> + Send + Sync + 'static) -> Self {
+        self.pre_execute_hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Registers a post-execute hook, run in registration order after an execution is recorded.
+    pub fn on_post_execute(
+        mut self,
+        hook: impl Fn(&Command, &ExecutionHistoryCreate) -> Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.post_execute_hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Sets the per-request timeout applied by the underlying `reqwest::Client`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the authentication scheme applied to every outgoing request.
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Sets the default `user` used when a request doesn't specify one explicitly.
+    pub fn default_user(mut self, user: impl Into<String>) -> Self {
+        self.default_user = Some(user.into());
+        self
+    }
+
+    /// Sets the default `hostname` used when a request doesn't specify one explicitly.
+    pub fn default_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.default_hostname = Some(hostname.into());
+        self
+    }
+
+    /// Sets the default `cwd` used when a request doesn't specify one explicitly.
+    pub fn default_cwd(mut self, cwd: impl Into<String>) -> Self {
+        self.default_cwd = Some(cwd.into());
+        self
+    }
+
+    /// Sets the result limit `get_suggestions` uses when a request's own `limit` is not positive.
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Sets how many times an idempotent `GET` is retried on a 5xx or connection error.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Builds the shared `reqwest::Client` and returns a fully configured `ApiClient`.
+    pub fn build(self) -> Result<ApiClient> {
+        let mut http_builder = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            http_builder = http_builder.timeout(timeout);
+        }
+        if let Some(tls) = &self.tls {
+            if let Some(ca_pem) = &tls.ca_cert_pem {
+                let ca_cert = reqwest::Certificate::from_pem(ca_pem)
+                    .context("Invalid CA certificate PEM")?;
+                http_builder = http_builder.add_root_certificate(ca_cert);
+            }
+            if let Some(identity_pem) = &tls.client_identity_pem {
+                let identity = reqwest::Identity::from_pem(identity_pem)
+                    .context("Invalid client identity PEM (expects certificate and key concatenated in one PEM)")?;
+                http_builder = http_builder.identity(identity);
+            }
+            if tls.accept_invalid_hostnames {
+                http_builder = http_builder.danger_accept_invalid_hostnames(true);
+            }
+        }
+        let http = http_builder
+            .build()
+            .context("Failed to build the HTTP client")?;
+
+        Ok(ApiClient {
+            http,
+            base_url: self.base_url,
+            auth: self.auth,
+            default_user: self.default_user,
+            default_hostname: self.default_hostname,
+            default_cwd: self.default_cwd,
+            limit: self.limit,
+            max_retries: self.max_retries,
+            pre_execute_hooks: self.pre_execute_hooks,
+            post_execute_hooks: self.post_execute_hooks,
+        })
+    }
+}
+
+/// One page of results from a list/search endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// The cursor to pass as `page` to fetch the next page, or `None` if this was the last page.
+    /// Derived client-side from whether the page came back full, since the server doesn't
+    /// currently report a total count.
+    pub next_cursor: Option<String>,
+    pub total: Option<i64>,
+}
+
+impl<T> Page<T> {
+    fn from_page_and_items(page: i64, per_page: i64, items: Vec<T>) -> Self {
+        let next_cursor = if items.len() as i64 >= per_page {
+            Some((page + 1).to_string())
+        } else {
+            None
+        };
+        Page {
+            items,
+            next_cursor,
+            total: None,
+        }
+    }
+}
+
 /// Represents a command returned from the server.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Command {
@@ -282,6 +721,10 @@ pub struct Command {
     pub use_count: i32,
     #[serde(default)]
     pub is_new: bool,
+    /// Free-text notes on why this command exists, set at save time. Searched by
+    /// `hp help --find` alongside `command_string`, `namespace`, and `name`.
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
 /// Represents a new command to be sent to the server.
@@ -294,6 +737,7 @@ pub struct NewCommand {
     pub cwd: Option<String>,
     pub hostname: Option<String>,
     pub scope: String,
+    pub description: Option<String>,
 }
 
 /// Represents a command update payload.
@@ -359,7 +803,7 @@ pub struct ProjectContextResponse {
 }
 
 /// Represents an execution history record for analytics.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct ExecutionHistoryCreate {
     pub command_id: i32,
     pub user: Option<String>,
@@ -370,3 +814,19 @@ pub struct ExecutionHistoryCreate {
     pub duration_ms: Option<i32>,
     pub exit_code: Option<i32>,
 }
+
+/// A previously-submitted execution history record, as read back for a single command (e.g. for
+/// the `Find` preview pane).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExecutionRecord {
+    pub id: i32,
+    pub command_id: i32,
+    pub user: Option<String>,
+    pub hostname: Option<String>,
+    pub cwd: Option<String>,
+    pub arguments: Option<String>,
+    pub execution_method: String,
+    pub duration_ms: Option<i32>,
+    pub exit_code: Option<i32>,
+    pub executed_at: DateTime<Utc>,
+}
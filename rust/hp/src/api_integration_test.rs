@@ -3,9 +3,10 @@ use super::api::{
     ApiClient, Command, ExecutionHistoryCreate, NewCommand, ProjectContextRequest,
     ProjectContextResponse, RecallByNameRequest, SuggestionsRequest,
 };
+use super::auth::Auth;
 use chrono::Utc;
 use serde_json::json;
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[tokio::test]
@@ -21,6 +22,7 @@ async fn test_save_command() {
         cwd: None,
         hostname: None,
         scope: "personal".to_string(),
+        description: None,
     };
     let response_body = Command {
         id: 1,
@@ -35,6 +37,7 @@ async fn test_save_command() {
         last_used_at: None,
         use_count: 0,
         is_new: false,
+        description: None,
     };
 
     Mock::given(method("POST"))
@@ -70,6 +73,7 @@ async fn test_recall_command() {
         last_used_at: None,
         use_count: 0,
         is_new: false,
+        description: None,
     };
 
     Mock::given(method("POST"))
@@ -114,6 +118,7 @@ async fn test_get_suggestions() {
         last_used_at: Some(Utc::now()),
         use_count: 5,
         is_new: false,
+        description: None,
     }];
 
     Mock::given(method("POST"))
@@ -189,6 +194,7 @@ async fn test_get_similar_commands() {
         last_used_at: Some(Utc::now()),
         use_count: 3,
         is_new: false,
+        description: None,
     }];
 
     Mock::given(method("GET"))
@@ -234,6 +240,7 @@ async fn test_recall_command_by_name() {
         last_used_at: Some(Utc::now()),
         use_count: 10,
         is_new: false,
+        description: None,
     };
 
     Mock::given(method("POST"))
@@ -346,6 +353,7 @@ async fn test_execute_command_tracking() {
         last_used_at: Some(Utc::now()),
         use_count: 1,
         is_new: false,
+        description: None,
     };
 
     Mock::given(method("POST"))
@@ -388,6 +396,29 @@ async fn test_error_handling_404() {
     assert!(error_msg.contains("not found"));
 }
 
+#[tokio::test]
+async fn test_error_handling_401_auth_failure() {
+    // Arrange
+    let server = MockServer::start().await;
+    let client = ApiClient::with_auth(server.uri(), Auth::bearer("stale-token"));
+
+    Mock::given(method("POST"))
+        .and(path("/commands/recall"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = client
+        .recall_command("ns", "command", "user", "host", "/tmp")
+        .await;
+
+    // Assert
+    assert!(result.is_err());
+    let error_msg = format!("{}", result.unwrap_err());
+    assert!(error_msg.contains("Authentication failed"));
+}
+
 #[tokio::test]
 async fn test_similar_commands_empty_result() {
     // Arrange
@@ -411,3 +442,77 @@ async fn test_similar_commands_empty_result() {
     let similar_commands = result.unwrap();
     assert_eq!(similar_commands.len(), 0);
 }
+
+fn command_fixture(id: i32) -> Command {
+    Command {
+        id,
+        command_string: "test".to_string(),
+        name: format!("test{id}"),
+        namespace: "test".to_string(),
+        user: Some("testuser".to_string()),
+        cwd: None,
+        hostname: None,
+        scope: "personal".to_string(),
+        created_at: Utc::now(),
+        last_used_at: None,
+        use_count: 0,
+        is_new: false,
+        description: None,
+    }
+}
+
+#[tokio::test]
+async fn test_get_all_user_commands_pages_through_a_full_library() {
+    // Arrange: a first page that comes back full (signalling more to fetch) and a second,
+    // shorter page that ends the walk.
+    let server = MockServer::start().await;
+    let client = ApiClient::new(server.uri());
+
+    // Matches ApiClient::ALL_USER_COMMANDS_PAGE_SIZE.
+    let page_size = 200;
+    let first_page: Vec<Command> = (0..page_size).map(command_fixture).collect();
+    let second_page: Vec<Command> = vec![command_fixture(page_size)];
+
+    Mock::given(method("GET"))
+        .and(path("/commands/all"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&first_page))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/commands/all"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&second_page))
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = client.get_all_user_commands("testuser").await;
+
+    // Assert
+    assert!(result.is_ok());
+    let commands = result.unwrap();
+    assert_eq!(commands.len(), page_size as usize + 1);
+}
+
+#[tokio::test]
+async fn test_get_all_user_commands_single_partial_page() {
+    // Arrange: a library smaller than one page shouldn't trigger a second request.
+    let server = MockServer::start().await;
+    let client = ApiClient::new(server.uri());
+    let only_page: Vec<Command> = vec![command_fixture(1), command_fixture(2)];
+
+    Mock::given(method("GET"))
+        .and(path("/commands/all"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&only_page))
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = client.get_all_user_commands("testuser").await;
+
+    // Assert
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().len(), 2);
+}
@@ -0,0 +1,183 @@
+//! A review/approval gate for shared-scope commands, so `execute_command_with_tracking` doesn't
+//! silently run arbitrary shell that another team member saved or later mutated on the server.
+//!
+//! Approvals are persisted under the config directory (see [`crate::config::config_dir`]) in an
+//! `approvals.json` file, mode `0600`: a map of `command_id -> sha256(command_string)` plus an
+//! HMAC-SHA256 signature over that map, keyed by a random signing key generated once and stored
+//! alongside it. The signature doesn't stop an attacker with local file access (they could just
+//! as easily patch `hp` itself), but it does catch the file being hand-edited or corrupted, which
+//! would otherwise silently widen what's considered "already reviewed".
+use crate::config::config_dir;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// One reviewed `(command_id, command_string)` pair.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ApprovalEntry {
+    /// Hex-encoded SHA-256 of the `command_string` at the time it was approved. Compared against
+    /// the command's *current* `command_string` on every check, so an edit on the server
+    /// invalidates the approval and re-triggers review.
+    command_hash: String,
+    approved_at: DateTime<Utc>,
+}
+
+/// The on-disk shape of `approvals.json`.
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct ApprovalFile {
+    /// Hex-encoded random key used to sign `entries`. Generated once on first use.
+    #[serde(default)]
+    signing_key: String,
+    #[serde(default)]
+    entries: HashMap<i32, ApprovalEntry>,
+    /// Hex-encoded HMAC-SHA256 of `entries`, computed with `signing_key`.
+    #[serde(default)]
+    signature: String,
+}
+
+/// Reviewed shared-scope commands, loaded from and persisted to `approvals.json`.
+pub struct ApprovalStore {
+    path: PathBuf,
+    file: ApprovalFile,
+}
+
+impl ApprovalStore {
+    /// Loads the approval store from the config directory, creating an empty (unsigned) one in
+    /// memory if the file doesn't exist yet. A file that fails its signature check is treated as
+    /// tampered or corrupted: its entries are discarded and a fresh signing key is generated, so
+    /// every command requires re-review rather than silently trusting a bad file.
+    pub fn load() -> Result<Self> {
+        let path = config_dir()
+            .context("Could not determine config directory for the approval store")?
+            .join("approvals.json");
+
+        let file = match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let parsed: ApprovalFile = serde_json::from_str(&contents)
+                    .with_context(|| format!("Invalid approval store at {}", path.display()))?;
+                if parsed.verify() {
+                    parsed
+                } else {
+                    eprintln!(
+                        "Warning: approval store at {} failed its signature check (hand-edited or \
+                         corrupted); resetting it, so every shared-scope command will need review again",
+                        path.display()
+                    );
+                    ApprovalFile::default()
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ApprovalFile::default(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()))
+            }
+        };
+
+        Ok(ApprovalStore { path, file })
+    }
+
+    /// Whether `command_id`'s current `command_string` matches what was approved for it. `false`
+    /// both when the command has never been approved and when it was approved but has since
+    /// changed on the server.
+    pub fn is_approved(&self, command_id: i32, command_string: &str) -> bool {
+        self.file
+            .entries
+            .get(&command_id)
+            .is_some_and(|entry| entry.command_hash == hash_command(command_string))
+    }
+
+    /// Records `command_id`/`command_string` as reviewed and persists the store.
+    pub fn approve(&mut self, command_id: i32, command_string: &str) -> Result<()> {
+        if self.file.signing_key.is_empty() {
+            self.file.signing_key = generate_signing_key();
+        }
+        self.file.entries.insert(
+            command_id,
+            ApprovalEntry {
+                command_hash: hash_command(command_string),
+                approved_at: Utc::now(),
+            },
+        );
+        self.file.resign();
+        self.persist()
+    }
+
+    fn persist(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        let contents = serde_json::to_string_pretty(&self.file)
+            .context("Failed to serialize the approval store")?;
+        fs::write(&self.path, contents)
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.path, fs::Permissions::from_mode(0o600))
+                .with_context(|| format!("Failed to set permissions on {}", self.path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+impl ApprovalFile {
+    /// Recomputes `signature` over `entries` with `signing_key`.
+    fn resign(&mut self) {
+        self.signature = sign_entries(&self.signing_key, &self.entries);
+    }
+
+    /// Checks `signature` against a fresh HMAC over `entries`. A file with no entries and no
+    /// signing key (i.e. freshly created, never approved anything) verifies trivially.
+    fn verify(&self) -> bool {
+        if self.entries.is_empty() && self.signing_key.is_empty() {
+            return true;
+        }
+        self.signature == sign_entries(&self.signing_key, &self.entries)
+    }
+}
+
+fn generate_signing_key() -> String {
+    use rand::RngCore;
+    let mut key = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut key);
+    key.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn sign_entries(signing_key: &str, entries: &HashMap<i32, ApprovalEntry>) -> String {
+    // Sort by command ID so the signed bytes are deterministic regardless of HashMap iteration
+    // order.
+    let mut ids: Vec<&i32> = entries.keys().collect();
+    ids.sort();
+    let canonical = ids
+        .into_iter()
+        .map(|id| format!("{id}:{}", entries[id].command_hash))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let mut mac = HmacSha256::new_from_slice(signing_key.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(canonical.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn hash_command(command_string: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(command_string.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -0,0 +1,191 @@
+//! Abstracts the operations `main` needs from a command store behind a [`Backend`] trait, so
+//! `hp` isn't hard-wired to a running server. [`crate::api::ApiClient`] implements it as a thin
+//! pass-through to the existing HTTP calls; [`crate::local`] implements it against an embedded
+//! SQLite file for fully offline use. Which one `main` constructs is chosen by
+//! [`crate::config::Settings::backend`].
+use crate::api::{
+    Command, CommandRename, CommandUpdate, ExecutionHistoryCreate, ExecutionRecord, NewCommand,
+    ProjectContextRequest, ProjectContextResponse, RecallByNameRequest, SuggestionsRequest,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+
+/// The set of command-store operations `main` drives, independent of whether they're served by
+/// the HTTP API or a local SQLite file.
+#[async_trait]
+pub trait Backend: Send + Sync {
+    /// Saves a new command, returning it with `is_new` set to whether it didn't already exist.
+    async fn save_command(&self, new_command: NewCommand) -> Result<Command>;
+    /// Searches for commands matching `query`, optionally narrowed by namespace/user/scope.
+    async fn get_commands(
+        &self,
+        query: &str,
+        namespace: Option<&str>,
+        user: Option<&str>,
+        scope: Option<&str>,
+    ) -> Result<Vec<Command>>;
+    /// Recalls a command by namespace and name for the given execution context.
+    async fn recall_command(
+        &self,
+        namespace: &str,
+        name: &str,
+        user: &str,
+        hostname: &str,
+        cwd: &str,
+    ) -> Result<Command>;
+    /// Lists all known namespaces.
+    async fn get_namespaces(&self) -> Result<Vec<String>>;
+    /// Deletes a command by ID, scoped to `user`'s permission to do so.
+    async fn delete_command(&self, command_id: i32, user: &str) -> Result<Command>;
+    /// Updates a command's command string.
+    async fn update_command(
+        &self,
+        command_id: i32,
+        user: &str,
+        command_update: CommandUpdate,
+    ) -> Result<Command>;
+    /// Lists every command belonging to `user`.
+    async fn get_all_user_commands(&self, user: &str) -> Result<Vec<Command>>;
+    /// Renames a command's namespace and/or name.
+    async fn rename_command(
+        &self,
+        command_id: i32,
+        user: &str,
+        command_rename: CommandRename,
+    ) -> Result<Command>;
+    /// Executes a command by ID, recording the access.
+    async fn execute_command(&self, command_id: i32, user: &str) -> Result<Command>;
+    /// Recalls a command by name with contextual matching.
+    async fn recall_command_by_name(&self, request: RecallByNameRequest) -> Result<Command>;
+    /// Gets contextual command suggestions.
+    async fn get_suggestions(&self, request: SuggestionsRequest) -> Result<Vec<Command>>;
+    /// Detects project context (namespace, project type, similar commands) for a directory.
+    async fn detect_project_context(
+        &self,
+        request: ProjectContextRequest,
+    ) -> Result<ProjectContextResponse>;
+    /// Gets commands similar to the given command ID.
+    async fn get_similar_commands(&self, command_id: i32, limit: Option<i32>) -> Result<Vec<Command>>;
+    /// Gets execution analytics, as a backend-specific JSON payload.
+    async fn get_execution_analytics(&self, user: Option<&str>, days: Option<i32>) -> Result<Value>;
+    /// Gets the most recent execution history records for a single command, newest first.
+    async fn get_execution_history(&self, command_id: i32, limit: i32) -> Result<Vec<ExecutionRecord>>;
+    /// Full-text search across every saved command's `command_string`, `namespace`, `name`, and
+    /// `description`, across the whole corpus. Backs `hp help --find`.
+    async fn find_commands_by_text(&self, query: &str) -> Result<Vec<Command>>;
+    /// Runs any registered pre-execute hooks against `command`. An `Err` aborts execution.
+    fn run_pre_execute_hooks(&self, command: &Command) -> Result<()>;
+    /// Records an execution history entry for analytics, then runs any registered post-execute
+    /// hooks.
+    async fn record_execution(
+        &self,
+        command: &Command,
+        execution: ExecutionHistoryCreate,
+    ) -> Result<Value>;
+}
+
+#[async_trait]
+impl Backend for crate::api::ApiClient {
+    async fn save_command(&self, new_command: NewCommand) -> Result<Command> {
+        self.save_command(new_command).await
+    }
+
+    async fn get_commands(
+        &self,
+        query: &str,
+        namespace: Option<&str>,
+        user: Option<&str>,
+        scope: Option<&str>,
+    ) -> Result<Vec<Command>> {
+        self.get_commands(query, namespace, user, scope).await
+    }
+
+    async fn recall_command(
+        &self,
+        namespace: &str,
+        name: &str,
+        user: &str,
+        hostname: &str,
+        cwd: &str,
+    ) -> Result<Command> {
+        self.recall_command(namespace, name, user, hostname, cwd).await
+    }
+
+    async fn get_namespaces(&self) -> Result<Vec<String>> {
+        self.get_namespaces().await
+    }
+
+    async fn delete_command(&self, command_id: i32, user: &str) -> Result<Command> {
+        self.delete_command(command_id, user).await
+    }
+
+    async fn update_command(
+        &self,
+        command_id: i32,
+        user: &str,
+        command_update: CommandUpdate,
+    ) -> Result<Command> {
+        self.update_command(command_id, user, command_update).await
+    }
+
+    async fn get_all_user_commands(&self, user: &str) -> Result<Vec<Command>> {
+        self.get_all_user_commands(user).await
+    }
+
+    async fn rename_command(
+        &self,
+        command_id: i32,
+        user: &str,
+        command_rename: CommandRename,
+    ) -> Result<Command> {
+        self.rename_command(command_id, user, command_rename).await
+    }
+
+    async fn execute_command(&self, command_id: i32, user: &str) -> Result<Command> {
+        self.execute_command(command_id, user).await
+    }
+
+    async fn recall_command_by_name(&self, request: RecallByNameRequest) -> Result<Command> {
+        self.recall_command_by_name(request).await
+    }
+
+    async fn get_suggestions(&self, request: SuggestionsRequest) -> Result<Vec<Command>> {
+        self.get_suggestions(request).await
+    }
+
+    async fn detect_project_context(
+        &self,
+        request: ProjectContextRequest,
+    ) -> Result<ProjectContextResponse> {
+        self.detect_project_context(request).await
+    }
+
+    async fn get_similar_commands(&self, command_id: i32, limit: Option<i32>) -> Result<Vec<Command>> {
+        self.get_similar_commands(command_id, limit).await
+    }
+
+    async fn get_execution_analytics(&self, user: Option<&str>, days: Option<i32>) -> Result<Value> {
+        self.get_execution_analytics(user, days).await
+    }
+
+    async fn get_execution_history(&self, command_id: i32, limit: i32) -> Result<Vec<ExecutionRecord>> {
+        self.get_execution_history(command_id, limit).await
+    }
+
+    async fn find_commands_by_text(&self, query: &str) -> Result<Vec<Command>> {
+        self.find_commands_by_text(query).await
+    }
+
+    fn run_pre_execute_hooks(&self, command: &Command) -> Result<()> {
+        self.run_pre_execute_hooks(command)
+    }
+
+    async fn record_execution(
+        &self,
+        command: &Command,
+        execution: ExecutionHistoryCreate,
+    ) -> Result<Value> {
+        self.record_execution(command, execution).await
+    }
+}
@@ -0,0 +1,218 @@
+//! `hp batch`: runs a list of saved command references (IDs or `namespace/name` pairs) in
+//! sequence or with bounded parallelism. Each entry goes through the same
+//! [`templating::resolve_arguments`] → [`secrets::resolve_secrets_with`] →
+//! [`crate::execute_command_with_tracking`] pipeline as `hp recall`, for users who keep a
+//! "runbook" of saved commands and want to fire a known sequence in one invocation.
+use crate::{api, backend, execute_command_with_tracking, secrets, telemetry, templating};
+use anyhow::{Context, Result};
+use comfy_table::Table;
+use futures::stream::{self, StreamExt};
+use std::time::Instant;
+
+/// The outcome of running one batch entry, printed as a row in the final summary table.
+struct EntryResult {
+    reference: String,
+    namespace: String,
+    name: String,
+    exit_code: Option<i32>,
+    duration_ms: u128,
+    error: Option<String>,
+}
+
+/// Resolves `reference` against the backend the same way [`crate::Commands::Recall`] does
+/// (ID first, then `namespace/name`), runs it without exiting the process on failure (`hp recall`
+/// passes `exit_on_failure = true`; batch entries pass `false` so the run can continue), and times
+/// the whole thing for the summary table.
+#[allow(clippy::too_many_arguments)]
+async fn run_entry(
+    api_client: &dyn backend::Backend,
+    telemetry: Option<&telemetry::TelemetryGuard>,
+    settings: &crate::config::Settings,
+    reference: &str,
+    user: &str,
+    hostname: &str,
+    cwd: &str,
+    interactive_approval: bool,
+) -> EntryResult {
+    let start = Instant::now();
+    let outcome = run_entry_inner(
+        api_client,
+        telemetry,
+        settings,
+        reference,
+        user,
+        hostname,
+        cwd,
+        interactive_approval,
+    )
+    .await;
+    let duration_ms = start.elapsed().as_millis();
+
+    match outcome {
+        Ok((command, exit_code)) => EntryResult {
+            reference: reference.to_string(),
+            namespace: command.namespace,
+            name: command.name,
+            exit_code: Some(exit_code),
+            duration_ms,
+            error: None,
+        },
+        Err(e) => EntryResult {
+            reference: reference.to_string(),
+            namespace: String::new(),
+            name: String::new(),
+            exit_code: None,
+            duration_ms,
+            error: Some(format!("{e:#}")),
+        },
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_entry_inner(
+    api_client: &dyn backend::Backend,
+    telemetry: Option<&telemetry::TelemetryGuard>,
+    settings: &crate::config::Settings,
+    reference: &str,
+    user: &str,
+    hostname: &str,
+    cwd: &str,
+    interactive_approval: bool,
+) -> Result<(api::Command, i32)> {
+    let command = if let Ok(command_id) = reference.parse::<i32>() {
+        api_client
+            .execute_command(command_id, user)
+            .await
+            .with_context(|| format!("No command with ID {command_id}"))?
+    } else {
+        let (namespace, name) = reference
+            .split_once('/')
+            .with_context(|| format!("'{reference}' isn't a command ID or a namespace/name pair"))?;
+        api_client
+            .recall_command(namespace, name, user, hostname, cwd)
+            .await
+            .with_context(|| format!("Failed to recall '{reference}'"))?
+    };
+
+    let templated_command = templating::resolve_arguments(&command.command_string, &[])?;
+    let providers =
+        secrets::providers_for_namespace(&settings.secret_providers, &command.namespace);
+    let resolved_command = secrets::resolve_secrets_with(&providers, &templated_command)?;
+
+    let exit_code = execute_command_with_tracking(
+        api_client,
+        telemetry,
+        settings,
+        &command,
+        user,
+        hostname,
+        cwd,
+        &resolved_command,
+        "batch",
+        &[],
+        false,
+        interactive_approval,
+    )
+    .await?;
+
+    Ok((command, exit_code))
+}
+
+/// Runs every entry in `references`. With `parallelism <= 1` entries run strictly in sequence,
+/// stopping at the first failure unless `continue_on_error`; with a higher `parallelism`, up to
+/// that many entries run concurrently instead (there's no single "first" failure to stop at once
+/// entries overlap, so all of them always run). Always prints a summary table, and returns an
+/// error if any entry didn't succeed so the process exit code reflects the batch as a whole.
+///
+/// Concurrent entries never prompt for approval: with several entries in flight at once, an
+/// interactive `[y/N]` prompt on shared stdin would just garble across them, so any entry whose
+/// command needs review fails outright (pointing at `hp approve`) instead of prompting, same as
+/// `settings.require_review`. Sequential (`parallelism <= 1`) runs keep prompting as before.
+pub async fn run(
+    api_client: &dyn backend::Backend,
+    telemetry: Option<&telemetry::TelemetryGuard>,
+    settings: &crate::config::Settings,
+    user: &str,
+    hostname: &str,
+    cwd: &str,
+    references: &[String],
+    continue_on_error: bool,
+    parallelism: usize,
+) -> Result<()> {
+    let parallelism = parallelism.max(1);
+    let interactive_approval = parallelism == 1;
+
+    let results = if parallelism == 1 {
+        let mut results = Vec::with_capacity(references.len());
+        for reference in references {
+            let result = run_entry(
+                api_client,
+                telemetry,
+                settings,
+                reference,
+                user,
+                hostname,
+                cwd,
+                interactive_approval,
+            )
+            .await;
+            let succeeded = result.exit_code == Some(0);
+            results.push(result);
+            if !succeeded && !continue_on_error {
+                break;
+            }
+        }
+        results
+    } else {
+        stream::iter(references)
+            .map(|reference| {
+                run_entry(
+                    api_client,
+                    telemetry,
+                    settings,
+                    reference,
+                    user,
+                    hostname,
+                    cwd,
+                    interactive_approval,
+                )
+            })
+            .buffered(parallelism)
+            .collect()
+            .await
+    };
+
+    let failures = results
+        .iter()
+        .filter(|r| r.exit_code != Some(0))
+        .count();
+    print_summary(&results);
+
+    if failures > 0 {
+        anyhow::bail!("{failures} of {} batch entries did not succeed", results.len());
+    }
+    Ok(())
+}
+
+/// Prints a `comfy_table` summary with one row per entry, matching the style `Analytics` already
+/// uses for its own tables.
+fn print_summary(results: &[EntryResult]) {
+    let mut table = Table::new();
+    table.set_header(vec!["Reference", "Namespace", "Name", "Status", "Duration"]);
+    for result in results {
+        let status = match (&result.error, result.exit_code) {
+            (Some(error), _) => format!("error: {error}"),
+            (None, Some(0)) => "ok".to_string(),
+            (None, Some(code)) => format!("exit {code}"),
+            (None, None) => "skipped".to_string(),
+        };
+        table.add_row(vec![
+            result.reference.clone(),
+            result.namespace.clone(),
+            result.name.clone(),
+            status,
+            format!("{}ms", result.duration_ms),
+        ]);
+    }
+    println!("{table}");
+}
@@ -1,37 +1,128 @@
 //! Handles loading configuration for the `hp` client.
 //!
 //! This module defines the `Settings` struct and the logic for loading it.
-//! Configuration is loaded from three locations (in order of precedence):
-//! 1. Global file in the user's config directory: ~/.config/hiproc/config.toml
-//! 2. File in the same directory as the binary: <binary_dir>/hiproc.toml
-//! 3. Local file in the current directory: ./hiproc.toml (highest precedence)
-use config::{Config, ConfigError, File};
+//! Configuration is loaded from four locations (in order of precedence, lowest first):
+//! 1. Global file in the user's config directory: `$XDG_CONFIG_HOME/hiproc/config.toml`,
+//!    falling back to `~/.config/hiproc/config.toml` if `XDG_CONFIG_HOME` is unset.
+//! 2. File in the same directory as the binary: `<binary_dir>/hiproc.toml`
+//! 3. Local file in the current directory: `./hiproc.toml`
+//! 4. Environment variables prefixed `HIPROC_` (e.g. `HIPROC_SERVER_URL`) — highest precedence
+use crate::history::RankWeights;
+use crate::manifest::{self, ManifestEcosystem};
+use crate::secrets::SecretProviderSettings;
+use crate::telemetry::TelemetryConfig;
+use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
 use std::env;
+use std::path::PathBuf;
+
+/// Which [`crate::backend::Backend`] implementation `hp` talks to.
+#[derive(Debug, Deserialize, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    /// The `hiproc` HTTP server, via [`crate::api::ApiClient`]. The default.
+    #[default]
+    Remote,
+    /// An offline, embedded-SQLite store, via [`crate::local::LocalBackend`]. Useful without a
+    /// running server, or entirely offline.
+    Local,
+}
+
+/// Transport-security options for talking to a `remote` backend behind an internal PKI, applied
+/// via [`crate::api::ApiClientBuilder::tls`]. All fields are optional; leaving every field unset
+/// (the default) makes `hp` trust only the system root CAs, same as before this existed.
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct TlsSettings {
+    /// PEM-encoded CA certificate bundle to trust in addition to the system roots, for a
+    /// self-signed or internally-issued server certificate.
+    pub ca_cert_path: Option<PathBuf>,
+    /// PEM file containing a client certificate and private key concatenated together, presented
+    /// for mutual TLS.
+    pub client_identity_path: Option<PathBuf>,
+    /// Skips server hostname verification. Only useful against test servers whose certificate
+    /// doesn't cover the hostname being dialed; never enable this for a production endpoint.
+    #[serde(default)]
+    pub accept_invalid_hostnames: bool,
+}
+
+/// Returns hiproc's config directory: `$XDG_CONFIG_HOME/hiproc`, falling back to
+/// `~/.config/hiproc` when `XDG_CONFIG_HOME` is unset. Shared by [`Settings`] (for
+/// `config.toml`) and the secret vault (for `secrets.vault`) so both live next to each other.
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    if let Ok(xdg_config_home) = env::var("XDG_CONFIG_HOME") {
+        if !xdg_config_home.is_empty() {
+            return Some(PathBuf::from(xdg_config_home).join("hiproc"));
+        }
+    }
+    home::home_dir().map(|home| home.join(".config").join("hiproc"))
+}
 
 /// Contains all configuration settings for the client.
 #[derive(Debug, Deserialize)]
 pub struct Settings {
-    /// The URL of the `hiproc` server.
+    /// The URL of the `hiproc` server. Only required when `backend` is `remote`.
+    #[serde(default)]
     pub server_url: String,
+    /// Additional regex patterns for redacting/ignoring secrets when reading shell history,
+    /// layered on top of hiproc's built-in default set.
+    #[serde(default)]
+    pub history_ignore: Vec<String>,
+    /// Feature weights for `HistoryManager::rank_recent`'s contextual scoring.
+    #[serde(default)]
+    pub rank_weights: RankWeights,
+    /// Per-namespace ordering of `{{secret:NAME}}` resolution providers (env, vault, OS keyring,
+    /// external command, interactive prompt).
+    #[serde(default)]
+    pub secret_providers: SecretProviderSettings,
+    /// Which [`BackendKind`] to store and recall commands through. Defaults to `remote`.
+    #[serde(default)]
+    pub backend: BackendKind,
+    /// Overrides where the `local` backend's SQLite file lives. Defaults to
+    /// `<config_dir>/local.db` (see [`crate::local::default_db_path`]) when unset.
+    pub local_db_path: Option<PathBuf>,
+    /// OpenTelemetry OTLP export of command execution spans and metrics (see
+    /// [`crate::telemetry`]). Off by default.
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    /// Order in which manifest ecosystems (see [`crate::manifest::ManifestEcosystem`]) are tried
+    /// when detecting a namespace from directory context. Defaults to
+    /// [`manifest::default_detector_order`]; override to make a different ecosystem win in a
+    /// polyglot monorepo.
+    #[serde(default = "manifest::default_detector_order")]
+    pub namespace_detectors: Vec<ManifestEcosystem>,
+    /// When `true`, running a command whose `scope` isn't `"personal"` and that hasn't already
+    /// been approved (see [`crate::approval::ApprovalStore`]) fails outright instead of prompting
+    /// for an interactive review. Off by default, so a solo/offline user never gets blocked by a
+    /// gate meant for shared team commands.
+    #[serde(default)]
+    pub require_review: bool,
+    /// Custom CA / mutual TLS settings for the `remote` backend (see [`TlsSettings`]). Unset by
+    /// default, meaning only the system root CAs are trusted.
+    #[serde(default)]
+    pub tls: TlsSettings,
+    /// Appends one JSON line per executed command (id, command string, and submitted execution
+    /// record) to this file via [`crate::api::ApiClientBuilder::on_post_execute`], for sites that
+    /// want an audit trail independent of the server's own execution-history table. Unset by
+    /// default; only takes effect against the `remote` backend.
+    pub audit_log_path: Option<PathBuf>,
 }
 
 impl Settings {
-    /// Creates a new `Settings` struct by loading configuration from files.
-    /// 
+    /// Creates a new `Settings` struct by loading configuration from files and the environment.
+    ///
     /// Configuration is loaded in order of precedence:
-    /// 1. Global config: ~/.config/hiproc/config.toml
+    /// 1. Global config: `$XDG_CONFIG_HOME/hiproc/config.toml` (or `~/.config/hiproc/config.toml`)
     /// 2. Binary-adjacent config: <binary_dir>/hiproc.toml
-    /// 3. Local config: ./hiproc.toml (highest precedence)
+    /// 3. Local config: ./hiproc.toml
+    /// 4. Environment variables prefixed `HIPROC_` (highest precedence)
     pub fn new() -> Result<Self, ConfigError> {
         let mut builder = Config::builder();
 
-        // 1. Add global config file from user's config directory
-        if let Some(mut config_path) = home::home_dir() {
-            config_path.push(".config");
-            config_path.push("hiproc");
-            config_path.push("config.toml");
-            builder = builder.add_source(File::from(config_path).required(false));
+        // 1. Add global config file from the XDG/user config directory
+        if let Some(config_dir) = config_dir() {
+            builder =
+                builder.add_source(File::from(config_dir.join("config.toml")).required(false));
         }
 
         // 2. Add config file from the same directory as the binary
@@ -43,10 +134,39 @@ impl Settings {
             }
         }
 
-        // 3. Add local config file (highest precedence)
+        // 3. Add local config file
         builder = builder.add_source(File::with_name("hiproc.toml").required(false));
 
+        // 4. Environment variables, e.g. HIPROC_SERVER_URL (highest precedence)
+        builder = builder.add_source(Environment::with_prefix("HIPROC"));
+
         let s = builder.build()?;
-        s.try_deserialize()
+        let settings: Settings = s.try_deserialize()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Validates settings that can't be expressed as simple deserialization constraints, so a
+    /// bad config surfaces as a clear startup error instead of an opaque request failure later.
+    fn validate(&self) -> Result<(), ConfigError> {
+        // The local backend never talks to a server, so an unset/placeholder server_url is fine.
+        if self.backend == BackendKind::Local {
+            return Ok(());
+        }
+
+        if self.server_url.trim().is_empty() {
+            return Err(ConfigError::Message(
+                "server_url must not be empty".to_string(),
+            ));
+        }
+
+        url::Url::parse(&self.server_url).map_err(|e| {
+            ConfigError::Message(format!(
+                "server_url '{}' is not a valid URL: {}",
+                self.server_url, e
+            ))
+        })?;
+
+        Ok(())
     }
 }
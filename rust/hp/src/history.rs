@@ -6,10 +6,75 @@ to enable quick-save workflows and last-command detection.
 */
 
 use anyhow::{Context, Result};
+use regex::{Regex, RegexSet};
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
 
+/// Built-in patterns that flag a history entry as likely containing a secret, so it is never
+/// offered up for a quick-save. Users can extend this list via `Settings::history_ignore`.
+const DEFAULT_IGNORE_PATTERNS: &[&str] = &[
+    r"(?i)(password|passwd|secret|token|api[_-]?key)\s*[:=]",
+    r"(?i)authorization:\s*(bearer|basic)\s",
+    r"(?i)\b(AWS|GCP|AZURE)_[A-Z_]*(SECRET|KEY|TOKEN)\b",
+    r"(?i)\bmysql\b.*-p\S",
+    r"-----BEGIN [A-Z ]*PRIVATE KEY-----",
+];
+
+/// A compiled set of redaction/ignore rules for shell history entries.
+///
+/// Matching is done once per candidate entry with a [`RegexSet`] (fast to evaluate against many
+/// patterns at once); the parallel `Vec<Regex>` is only consulted when masking matched
+/// substrings rather than dropping the entry outright.
+#[derive(Clone)]
+pub struct IgnoreRules {
+    patterns: Vec<Regex>,
+    set: RegexSet,
+}
+
+impl IgnoreRules {
+    /// Builds the rule set from the built-in defaults plus any additional patterns (e.g. from
+    /// `Settings::history_ignore`).
+    pub fn new(extra_patterns: &[String]) -> Result<Self> {
+        let pattern_strs: Vec<String> = DEFAULT_IGNORE_PATTERNS
+            .iter()
+            .map(|s| s.to_string())
+            .chain(extra_patterns.iter().cloned())
+            .collect();
+
+        let patterns = pattern_strs
+            .iter()
+            .map(|p| Regex::new(p).with_context(|| format!("Invalid history_ignore pattern: {p}")))
+            .collect::<Result<Vec<_>>>()?;
+        let set = RegexSet::new(&pattern_strs).context("Failed to compile history ignore pattern set")?;
+
+        Ok(Self { patterns, set })
+    }
+
+    /// Returns `true` if `command` matches any ignore pattern and should be dropped entirely.
+    pub fn matches(&self, command: &str) -> bool {
+        self.set.is_match(command)
+    }
+
+    /// Masks any matched substrings in `command` with `***`, leaving the rest intact.
+    #[allow(dead_code)] // exposed for callers that want to show a redacted entry instead of dropping it
+    pub fn redact(&self, command: &str) -> String {
+        let mut redacted = command.to_string();
+        for pattern in &self.patterns {
+            redacted = pattern.replace_all(&redacted, "***").into_owned();
+        }
+        redacted
+    }
+}
+
+impl Default for IgnoreRules {
+    fn default() -> Self {
+        Self::new(&[]).expect("default ignore patterns are valid regexes")
+    }
+}
+
 /// Represents different shell types that we can integrate with.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ShellType {
@@ -58,24 +123,74 @@ impl ShellType {
     }
 }
 
+/// Feature weights for [`HistoryManager::rank_recent`], a transparent linear scoring function
+/// (no ML dependency) inspired by McFly: `score = frequency*w_frequency + recency*w_recency +
+/// cwd_match*w_cwd_match + complexity*w_complexity`, where each feature is normalized to `[0, 1]`
+/// before weighting so the weights stay comparable across features. Overridable from
+/// `Settings::rank_weights`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct RankWeights {
+    /// Weight for how often the exact command appears in history.
+    pub frequency: f64,
+    /// Weight for exponential time decay since the command was last run.
+    pub recency: f64,
+    /// Weight for whether the command was previously run in the current working directory.
+    pub cwd_match: f64,
+    /// Weight for the command's length/complexity (longer commands are more worth saving).
+    pub complexity: f64,
+}
+
+impl Default for RankWeights {
+    fn default() -> Self {
+        Self {
+            frequency: 1.0,
+            recency: 1.0,
+            cwd_match: 0.5,
+            complexity: 0.25,
+        }
+    }
+}
+
+/// A single history entry with whatever metadata the source shell's format provides.
+#[derive(Debug, Clone)]
+struct HistoryRecord {
+    command: String,
+    /// Unix timestamp, when the shell's history format records one (zsh, fish).
+    timestamp: Option<i64>,
+    /// Working directory the command was run in, when the shell's history format records one.
+    /// None of bash/zsh/fish's default formats capture this today; the field exists so the
+    /// `cwd_match` feature activates automatically once a source that records it is added.
+    cwd: Option<String>,
+}
+
 /// Shell history manager for reading command history.
 pub struct HistoryManager {
     shell_type: ShellType,
     history_path: Option<PathBuf>,
+    ignore: IgnoreRules,
 }
 
 impl HistoryManager {
-    /// Create a new history manager for the current shell.
+    /// Create a new history manager for the current shell, using only the built-in ignore rules.
     pub fn new() -> Self {
+        Self::with_ignore_patterns(&[]).expect("default ignore patterns are valid regexes")
+    }
+
+    /// Create a new history manager for the current shell, extending the built-in ignore rules
+    /// with `extra_patterns` (typically `Settings::history_ignore`).
+    pub fn with_ignore_patterns(extra_patterns: &[String]) -> Result<Self> {
         let shell_type = ShellType::detect();
         let history_path = shell_type.history_file_path();
-        
-        Self {
+        let ignore = IgnoreRules::new(extra_patterns)?;
+
+        Ok(Self {
             shell_type,
             history_path,
-        }
+            ignore,
+        })
     }
-    
+
     /// Create a history manager for a specific shell type.
     #[allow(dead_code)]  // Will be used in Phase 3 for advanced features
     pub fn for_shell(shell_type: ShellType) -> Self {
@@ -83,75 +198,354 @@ impl HistoryManager {
         Self {
             shell_type,
             history_path,
+            ignore: IgnoreRules::default(),
         }
     }
-    
+
     /// Get the last executed command from shell history.
     pub fn get_last_command(&self) -> Result<Option<String>> {
         let path = self.history_path.as_ref()
             .context("No history file path available for this shell")?;
-            
+
         if !path.exists() {
             return Ok(None);
         }
-        
-        let content = fs::read_to_string(path)
-            .context("Failed to read history file")?;
-            
+
         match self.shell_type {
-            ShellType::Bash => self.parse_bash_history(&content),
-            ShellType::Zsh => self.parse_zsh_history(&content),
-            ShellType::Fish => self.parse_fish_history(&content),
+            ShellType::Bash => {
+                let content = fs::read_to_string(path).context("Failed to read history file")?;
+                self.parse_bash_history(&content)
+            }
+            ShellType::Zsh => {
+                let bytes = fs::read(path).context("Failed to read history file")?;
+                let content = Self::decode_zsh_metafied(&bytes);
+                self.parse_zsh_history(&content)
+            }
+            ShellType::Fish => {
+                let content = fs::read_to_string(path).context("Failed to read history file")?;
+                self.parse_fish_history(&content)
+            }
             ShellType::Unknown => Ok(None),
         }
     }
-    
+
     /// Get the last N commands from shell history.
     #[allow(dead_code)]  // Will be used in Phase 3 for command recommendations
     pub fn get_recent_commands(&self, count: usize) -> Result<Vec<String>> {
         let path = self.history_path.as_ref()
             .context("No history file path available for this shell")?;
-            
+
         if !path.exists() {
             return Ok(vec![]);
         }
-        
-        let content = fs::read_to_string(path)
-            .context("Failed to read history file")?;
-            
+
         match self.shell_type {
-            ShellType::Bash => self.parse_bash_recent(&content, count),
-            ShellType::Zsh => self.parse_zsh_recent(&content, count),
-            ShellType::Fish => self.parse_fish_recent(&content, count),
+            ShellType::Bash => {
+                let content = fs::read_to_string(path).context("Failed to read history file")?;
+                self.parse_bash_recent(&content, count)
+            }
+            ShellType::Zsh => {
+                let bytes = fs::read(path).context("Failed to read history file")?;
+                let content = Self::decode_zsh_metafied(&bytes);
+                self.parse_zsh_recent(&content, count)
+            }
+            ShellType::Fish => {
+                let content = fs::read_to_string(path).context("Failed to read history file")?;
+                self.parse_fish_recent(&content, count)
+            }
             ShellType::Unknown => Ok(vec![]),
         }
     }
+
+    /// Ranks recent commands by a weighted combination of frequency, recency, current-directory
+    /// match, and length/complexity, rather than returning raw file-order recency like
+    /// [`get_recent_commands`](Self::get_recent_commands). Candidates are scored over the full
+    /// available history (so frequency/recency are meaningful) and the top `count` are returned,
+    /// most relevant first.
+    pub fn rank_recent(&self, count: usize, weights: RankWeights) -> Result<Vec<String>> {
+        let path = match self.history_path.as_ref() {
+            Some(p) if p.exists() => p,
+            _ => return Ok(vec![]),
+        };
+
+        let records = match self.shell_type {
+            ShellType::Bash => {
+                let content = fs::read_to_string(path).context("Failed to read history file")?;
+                self.collect_bash_records(&content)
+            }
+            ShellType::Zsh => {
+                let bytes = fs::read(path).context("Failed to read history file")?;
+                let content = Self::decode_zsh_metafied(&bytes);
+                self.collect_zsh_records(&content)
+            }
+            ShellType::Fish => {
+                let content = fs::read_to_string(path).context("Failed to read history file")?;
+                self.collect_fish_records(&content)
+            }
+            ShellType::Unknown => return Ok(vec![]),
+        };
+
+        if records.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let cwd = env::current_dir()
+            .ok()
+            .and_then(|p| p.to_str().map(|s| s.to_string()));
+
+        // Use the newest timestamp present as "now" so scoring stays deterministic and testable
+        // instead of depending on the wall clock at call time.
+        let now = records.iter().filter_map(|r| r.timestamp).max();
+        let max_len = records.iter().map(|r| r.command.len()).max().unwrap_or(1).max(1) as f64;
+
+        let mut frequency: HashMap<&str, u32> = HashMap::new();
+        for r in &records {
+            *frequency.entry(r.command.as_str()).or_insert(0) += 1;
+        }
+        let max_frequency = frequency.values().copied().max().unwrap_or(1).max(1) as f64;
+
+        // Keep the most recent record per unique command so recency/cwd reflect its last use.
+        let mut latest: HashMap<String, &HistoryRecord> = HashMap::new();
+        for r in &records {
+            latest
+                .entry(r.command.clone())
+                .and_modify(|existing| {
+                    if r.timestamp.unwrap_or(0) >= existing.timestamp.unwrap_or(0) {
+                        *existing = r;
+                    }
+                })
+                .or_insert(r);
+        }
+
+        let mut scored: Vec<(f64, String)> = latest
+            .into_values()
+            .map(|r| {
+                let freq_score = frequency[r.command.as_str()] as f64 / max_frequency;
+
+                let recency_score = match (r.timestamp, now) {
+                    (Some(ts), Some(now)) if now > 0 => {
+                        let age_secs = (now - ts).max(0) as f64;
+                        // Exponential decay with a one-day time constant (1/e after 24 hours).
+                        (-age_secs / 86_400.0).exp()
+                    }
+                    _ => 0.0,
+                };
+
+                let cwd_score = match (&r.cwd, &cwd) {
+                    (Some(a), Some(b)) if a == b => 1.0,
+                    _ => 0.0,
+                };
+
+                let complexity_score = r.command.len() as f64 / max_len;
+
+                let score = freq_score * weights.frequency
+                    + recency_score * weights.recency
+                    + cwd_score * weights.cwd_match
+                    + complexity_score * weights.complexity;
+
+                (score, r.command.clone())
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        Ok(scored.into_iter().take(count).map(|(_, cmd)| cmd).collect())
+    }
+
+    /// Collects bash history as [`HistoryRecord`]s (bash's default format has no timestamps).
+    fn collect_bash_records(&self, content: &str) -> Vec<HistoryRecord> {
+        Self::join_bash_continuations(content)
+            .into_iter()
+            .filter(|line| !line.trim().is_empty())
+            .filter(|line| !line.starts_with('#'))
+            .filter(|line| !self.ignore.matches(line))
+            .map(|command| HistoryRecord {
+                command,
+                timestamp: None,
+                cwd: None,
+            })
+            .collect()
+    }
+
+    /// Collects zsh history as [`HistoryRecord`]s, extracting the `: <timestamp>:<elapsed>;`
+    /// prefix when present.
+    fn collect_zsh_records(&self, content: &str) -> Vec<HistoryRecord> {
+        Self::join_zsh_continuations(content)
+            .into_iter()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                if let Some(rest) = line.strip_prefix(": ") {
+                    let (meta, command) = rest.split_once(';')?;
+                    let timestamp = meta.split(':').next()?.parse::<i64>().ok();
+                    Some(HistoryRecord {
+                        command: command.to_string(),
+                        timestamp,
+                        cwd: None,
+                    })
+                } else {
+                    Some(HistoryRecord {
+                        command: line,
+                        timestamp: None,
+                        cwd: None,
+                    })
+                }
+            })
+            .filter(|r| !self.ignore.matches(&r.command))
+            .collect()
+    }
+
+    /// Collects fish history as [`HistoryRecord`]s, pairing each `- cmd:` with its `when:` entry.
+    fn collect_fish_records(&self, content: &str) -> Vec<HistoryRecord> {
+        let mut records = Vec::new();
+        let mut pending_command: Option<String> = None;
+
+        for line in content.lines() {
+            if let Some(cmd) = line.strip_prefix("- cmd: ") {
+                if let Some(command) = pending_command.take() {
+                    records.push(HistoryRecord {
+                        command,
+                        timestamp: None,
+                        cwd: None,
+                    });
+                }
+                pending_command = Some(cmd.to_string());
+            } else if let Some(when) = line.trim_start().strip_prefix("when: ") {
+                if let Some(command) = pending_command.take() {
+                    records.push(HistoryRecord {
+                        command,
+                        timestamp: when.trim().parse::<i64>().ok(),
+                        cwd: None,
+                    });
+                }
+            }
+        }
+        if let Some(command) = pending_command.take() {
+            records.push(HistoryRecord {
+                command,
+                timestamp: None,
+                cwd: None,
+            });
+        }
+
+        records.retain(|r| !self.ignore.matches(&r.command));
+        records
+    }
+
+    /// Decodes zsh's "metafied" history encoding.
+    ///
+    /// Zsh escapes certain bytes in `$HISTFILE` by inserting a meta byte `0x83` before a byte
+    /// that must then be XOR-ed with `0x20` to recover the original character. We walk the raw
+    /// buffer, undo that escaping, then lossily decode to UTF-8 so malformed sequences degrade
+    /// gracefully instead of erroring the whole read.
+    fn decode_zsh_metafied(bytes: &[u8]) -> String {
+        const META: u8 = 0x83;
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut iter = bytes.iter().copied();
+        while let Some(b) = iter.next() {
+            if b == META {
+                if let Some(next) = iter.next() {
+                    decoded.push(next ^ 0x20);
+                }
+            } else {
+                decoded.push(b);
+            }
+        }
+        String::from_utf8_lossy(&decoded).into_owned()
+    }
     
+    /// Returns `true` if `line` ends in an odd number of trailing backslashes, i.e. a final
+    /// backslash that is itself unescaped and therefore continues onto the next physical line.
+    fn ends_with_unescaped_backslash(line: &str) -> bool {
+        line.chars().rev().take_while(|&c| c == '\\').count() % 2 == 1
+    }
+
+    /// Returns `true` if `line` begins a new zsh `EXTENDED_HISTORY` record
+    /// (`: <timestamp>:<elapsed>;<command>`).
+    fn is_zsh_record_start(line: &str) -> bool {
+        let re = Regex::new(r"^: \d+:\d+;").expect("valid regex");
+        re.is_match(line)
+    }
+
+    /// Joins physical lines that end in an unescaped trailing backslash into one logical line,
+    /// reconstructing multiline bash history entries (heredocs, `for` loops, etc).
+    fn join_bash_continuations(content: &str) -> Vec<String> {
+        let mut result = Vec::new();
+        let mut pending: Option<String> = None;
+        for line in content.lines() {
+            let mut current = match pending.take() {
+                Some(mut p) => {
+                    p.push('\n');
+                    p.push_str(line);
+                    p
+                }
+                None => line.to_string(),
+            };
+            if Self::ends_with_unescaped_backslash(&current) {
+                current.pop();
+                pending = Some(current);
+            } else {
+                result.push(current);
+            }
+        }
+        if let Some(p) = pending {
+            result.push(p);
+        }
+        result
+    }
+
+    /// Joins zsh `EXTENDED_HISTORY` continuation lines (trailing unescaped backslash, and the
+    /// next physical line not itself starting a new `: <timestamp>:<elapsed>;` record) into one
+    /// logical record before the `: ts:elapsed;command` prefix is stripped.
+    fn join_zsh_continuations(content: &str) -> Vec<String> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut result = Vec::new();
+        let mut i = 0;
+        while i < lines.len() {
+            let mut current = lines[i].to_string();
+            while Self::ends_with_unescaped_backslash(&current) {
+                let next_idx = i + 1;
+                if next_idx >= lines.len() || Self::is_zsh_record_start(lines[next_idx]) {
+                    break;
+                }
+                current.pop();
+                current.push('\n');
+                current.push_str(lines[next_idx]);
+                i = next_idx;
+            }
+            result.push(current);
+            i += 1;
+        }
+        result
+    }
+
     /// Parse bash history format (simple line-based).
     fn parse_bash_history(&self, content: &str) -> Result<Option<String>> {
-        Ok(content.lines()
+        Ok(Self::join_bash_continuations(content)
+            .into_iter()
             .filter(|line| !line.trim().is_empty())
             .filter(|line| !line.starts_with('#'))  // Skip comments
-            .last()
-            .map(|s| s.to_string()))
+            .filter(|line| !self.ignore.matches(line))
+            .last())
     }
-    
+
     /// Parse recent bash commands.
     #[allow(dead_code)]
     fn parse_bash_recent(&self, content: &str, count: usize) -> Result<Vec<String>> {
-        let commands: Vec<String> = content.lines()
+        let commands: Vec<String> = Self::join_bash_continuations(content)
+            .into_iter()
             .filter(|line| !line.trim().is_empty())
             .filter(|line| !line.starts_with('#'))
-            .map(|s| s.to_string())
+            .filter(|line| !self.ignore.matches(line))
             .collect();
-            
+
         Ok(commands.into_iter().rev().take(count).rev().collect())
     }
-    
+
     /// Parse zsh history format (includes timestamps).
     fn parse_zsh_history(&self, content: &str) -> Result<Option<String>> {
         // Zsh history format: : 1234567890:0;command
-        let last_command = content.lines()
+        let last_command = Self::join_zsh_continuations(content)
+            .into_iter()
             .filter(|line| !line.trim().is_empty())
             .filter_map(|line| {
                 if line.starts_with(':') && line.contains(';') {
@@ -159,28 +553,31 @@ impl HistoryManager {
                     line.splitn(2, ';').nth(1).map(|s| s.to_string())
                 } else {
                     // Fallback to treating as simple command
-                    Some(line.to_string())
+                    Some(line)
                 }
             })
+            .filter(|command| !self.ignore.matches(command))
             .last();
-            
+
         Ok(last_command)
     }
-    
+
     /// Parse recent zsh commands.
     #[allow(dead_code)]
     fn parse_zsh_recent(&self, content: &str, count: usize) -> Result<Vec<String>> {
-        let commands: Vec<String> = content.lines()
+        let commands: Vec<String> = Self::join_zsh_continuations(content)
+            .into_iter()
             .filter(|line| !line.trim().is_empty())
             .filter_map(|line| {
                 if line.starts_with(':') && line.contains(';') {
                     line.splitn(2, ';').nth(1).map(|s| s.to_string())
                 } else {
-                    Some(line.to_string())
+                    Some(line)
                 }
             })
+            .filter(|command| !self.ignore.matches(command))
             .collect();
-            
+
         Ok(commands.into_iter().rev().take(count).rev().collect())
     }
     
@@ -190,27 +587,31 @@ impl HistoryManager {
         // - cmd: command here
         //   when: 1234567890
         let mut last_command = None;
-        
+
         for line in content.lines() {
             if let Some(cmd) = line.strip_prefix("- cmd: ") {
-                last_command = Some(cmd.to_string());
+                if !self.ignore.matches(cmd) {
+                    last_command = Some(cmd.to_string());
+                }
             }
         }
-        
+
         Ok(last_command)
     }
-    
+
     /// Parse recent fish commands.
     #[allow(dead_code)]
     fn parse_fish_recent(&self, content: &str, count: usize) -> Result<Vec<String>> {
         let mut commands = Vec::new();
-        
+
         for line in content.lines() {
             if let Some(cmd) = line.strip_prefix("- cmd: ") {
-                commands.push(cmd.to_string());
+                if !self.ignore.matches(cmd) {
+                    commands.push(cmd.to_string());
+                }
             }
         }
-        
+
         Ok(commands.into_iter().rev().take(count).rev().collect())
     }
     
@@ -290,6 +691,94 @@ mod tests {
         assert_eq!(recent, vec!["first command", "second command"]);
     }
     
+    #[test]
+    fn test_rank_recent_prefers_frequent_and_recent() {
+        let content = ": 1000:0;git status\n\
+             : 2000:0;git status\n\
+             : 3000:0;echo once\n\
+             : 4000:0;git status\n";
+        let path = std::env::temp_dir().join(format!(
+            "hp_rank_recent_test_{}.zsh_history",
+            std::process::id()
+        ));
+        std::fs::write(&path, content).unwrap();
+        let manager = HistoryManager {
+            shell_type: ShellType::Zsh,
+            history_path: Some(path.clone()),
+            ignore: IgnoreRules::default(),
+        };
+
+        let ranked = manager.rank_recent(2, RankWeights::default()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(ranked.first(), Some(&"git status".to_string()));
+        assert_eq!(ranked.len(), 2);
+    }
+
+    #[test]
+    fn test_ignore_rules_drop_secrets() {
+        let manager = HistoryManager::for_shell(ShellType::Bash);
+        let content = "ls -la\nexport AWS_SECRET_ACCESS_KEY=abc123\necho done\n";
+        let last = manager.parse_bash_history(content).unwrap();
+        assert_eq!(last, Some("echo done".to_string()));
+
+        let recent = manager.parse_bash_recent(content, 10).unwrap();
+        assert_eq!(recent, vec!["ls -la".to_string(), "echo done".to_string()]);
+    }
+
+    #[test]
+    fn test_ignore_rules_custom_pattern() {
+        let manager =
+            HistoryManager::with_ignore_patterns(&["frobnicate".to_string()]).unwrap();
+        assert!(manager.ignore.matches("frobnicate --now"));
+        assert!(manager.ignore.matches("export AWS_SECRET_ACCESS_KEY=x"));
+        assert!(!manager.ignore.matches("ls -la"));
+    }
+
+    #[test]
+    fn test_ignore_rules_redact() {
+        let rules = IgnoreRules::default();
+        let redacted = rules.redact("export AWS_SECRET_ACCESS_KEY=abc123");
+        assert!(!redacted.contains("AWS_SECRET_ACCESS_KEY"));
+        assert!(redacted.contains("***"));
+    }
+
+    #[test]
+    fn test_bash_multiline_continuation() {
+        let manager = HistoryManager::for_shell(ShellType::Bash);
+        let content = "echo one\nfor i in 1 2 3; do \\\n  echo $i; \\\ndone\necho two\n";
+        let last = manager.parse_bash_history(content).unwrap();
+        assert_eq!(last, Some("echo two".to_string()));
+
+        let recent = manager.parse_bash_recent(content, 2).unwrap();
+        assert_eq!(
+            recent,
+            vec!["for i in 1 2 3; do \n  echo $i; \ndone".to_string(), "echo two".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_zsh_multiline_continuation() {
+        let manager = HistoryManager::for_shell(ShellType::Zsh);
+        let content = ": 1111:0;echo one\n: 2222:0;for i in 1 2 3; do \\\n  echo $i; \\\ndone\n: 3333:0;echo two\n";
+        let recent = manager.parse_zsh_recent(content, 3).unwrap();
+        assert_eq!(
+            recent,
+            vec![
+                "echo one".to_string(),
+                "for i in 1 2 3; do \n  echo $i; \ndone".to_string(),
+                "echo two".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_zsh_metafied() {
+        // A literal 0x09 (tab) is stored as the meta byte 0x83 followed by 0x09 ^ 0x20.
+        let raw = [b':', b' ', 0x83, 0x09 ^ 0x20, b'x'];
+        let decoded = HistoryManager::decode_zsh_metafied(&raw);
+        assert_eq!(decoded.as_bytes(), &[b':', b' ', 0x09, b'x']);
+    }
+
     #[test]
     fn test_empty_history() {
         let manager = HistoryManager::for_shell(ShellType::Bash);
@@ -0,0 +1,216 @@
+//! Generic bulk-import of shell history into hiproc procedures.
+//!
+//! Unlike [`history::HistoryManager`](crate::history::HistoryManager), which only reaches for the
+//! last or most recent N commands, an [`Importer`] is built to walk an *entire* history file once,
+//! reporting a size hint up front (for progress bars) and yielding entries one at a time so a large
+//! history can be imported in bounded-size batches instead of being materialized all at once.
+use anyhow::Result;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+
+/// A single parsed history entry ready to be offered up for import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The command text of one history line, with zsh's `: <timestamp>:<elapsed>;` or fish's
+    /// `- cmd: ` prefix removed. Multi-line entries are not joined and escapes are not decoded.
+    pub command: String,
+}
+
+/// Common interface for shell-specific history importers.
+///
+/// Implementations wrap a `BufReader` over any `Read + Seek` source (a file, but just as easily an
+/// `io::Cursor` over an in-memory fixture in tests) and yield entries via `Iterator`.
+pub trait Importer: Iterator<Item = Result<HistoryEntry>> {
+    /// An upper-bound count of entries in the source, computed once up front by counting lines.
+    /// Used to drive progress bars; it is a hint, not a guarantee, since some shells fold several
+    /// physical lines into one logical entry.
+    fn size_hint_total(&self) -> usize;
+}
+
+/// Counts the lines in `reader` (a last line without a trailing newline counts too), then
+/// rewinds it to the start.
+fn count_lines<R: Read + Seek>(reader: &mut R) -> Result<usize> {
+    let mut buf_reader = BufReader::new(&mut *reader);
+    let mut count = 0usize;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let bytes_read = buf_reader.read_line(&mut line)?;
+        if bytes_read == 0 {
+            break;
+        }
+        count += 1;
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(count)
+}
+
+/// Imports plain line-per-command bash history.
+pub struct BashImporter<R: Read + Seek> {
+    reader: BufReader<R>,
+    total: usize,
+}
+
+impl<R: Read + Seek> BashImporter<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        let total = count_lines(&mut reader)?;
+        Ok(Self {
+            reader: BufReader::new(reader),
+            total,
+        })
+    }
+}
+
+impl<R: Read + Seek> Iterator for BashImporter<R> {
+    type Item = Result<HistoryEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {
+                    let trimmed = line.trim_end_matches(['\n', '\r']);
+                    if trimmed.trim().is_empty() || trimmed.starts_with('#') {
+                        continue;
+                    }
+                    return Some(Ok(HistoryEntry {
+                        command: trimmed.to_string(),
+                    }));
+                }
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Importer for BashImporter<R> {
+    fn size_hint_total(&self) -> usize {
+        self.total
+    }
+}
+
+/// Imports zsh history (`: <timestamp>:<elapsed>;<command>` records).
+pub struct ZshImporter<R: Read + Seek> {
+    reader: BufReader<R>,
+    total: usize,
+}
+
+impl<R: Read + Seek> ZshImporter<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        let total = count_lines(&mut reader)?;
+        Ok(Self {
+            reader: BufReader::new(reader),
+            total,
+        })
+    }
+}
+
+impl<R: Read + Seek> Iterator for ZshImporter<R> {
+    type Item = Result<HistoryEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {
+                    let trimmed = line.trim_end_matches(['\n', '\r']);
+                    if trimmed.trim().is_empty() {
+                        continue;
+                    }
+                    let command = if trimmed.starts_with(':') && trimmed.contains(';') {
+                        trimmed.splitn(2, ';').nth(1).unwrap_or(trimmed)
+                    } else {
+                        trimmed
+                    };
+                    return Some(Ok(HistoryEntry {
+                        command: command.to_string(),
+                    }));
+                }
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Importer for ZshImporter<R> {
+    fn size_hint_total(&self) -> usize {
+        self.total
+    }
+}
+
+/// Imports fish history (`- cmd: <command>` YAML-like records).
+pub struct FishImporter<R: Read + Seek> {
+    reader: BufReader<R>,
+    total: usize,
+}
+
+impl<R: Read + Seek> FishImporter<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        let total = count_lines(&mut reader)?;
+        Ok(Self {
+            reader: BufReader::new(reader),
+            total,
+        })
+    }
+}
+
+impl<R: Read + Seek> Iterator for FishImporter<R> {
+    type Item = Result<HistoryEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {
+                    let trimmed = line.trim_end_matches(['\n', '\r']);
+                    if let Some(cmd) = trimmed.strip_prefix("- cmd: ") {
+                        return Some(Ok(HistoryEntry {
+                            command: cmd.to_string(),
+                        }));
+                    }
+                    // Any other line (e.g. `  when: ...`) isn't a new entry; keep scanning.
+                }
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Importer for FishImporter<R> {
+    fn size_hint_total(&self) -> usize {
+        self.total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_bash_importer() {
+        let data = "git status\n\n# a comment\nls -la\n";
+        let importer = BashImporter::new(Cursor::new(data)).unwrap();
+        assert_eq!(importer.size_hint_total(), 4);
+        let entries: Vec<_> = importer.map(|e| e.unwrap().command).collect();
+        assert_eq!(entries, vec!["git status", "ls -la"]);
+    }
+
+    #[test]
+    fn test_zsh_importer() {
+        let data = ": 1234567890:0;git status\n: 1234567891:0;ls -la\n";
+        let importer = ZshImporter::new(Cursor::new(data)).unwrap();
+        let entries: Vec<_> = importer.map(|e| e.unwrap().command).collect();
+        assert_eq!(entries, vec!["git status", "ls -la"]);
+    }
+
+    #[test]
+    fn test_fish_importer() {
+        let data = "- cmd: git status\n  when: 1234567890\n- cmd: ls -la\n  when: 1234567891\n";
+        let importer = FishImporter::new(Cursor::new(data)).unwrap();
+        let entries: Vec<_> = importer.map(|e| e.unwrap().command).collect();
+        assert_eq!(entries, vec!["git status", "ls -la"]);
+    }
+}
@@ -0,0 +1,232 @@
+//! An explorable `hp interactive` REPL for users who don't remember exact IDs: a rustyline
+//! [`Editor`] whose [`ReplHelper`] completes `namespace/name` pairs and numeric IDs, and hints the
+//! resolved `command_string` inline as you type a name, so you can preview a command before
+//! running it. Pressing enter resolves the line the same way [`crate::Commands::Recall`] does:
+//! [`crate::templating::resolve_arguments`], then [`crate::secrets::resolve_secrets_with`], then
+//! [`crate::execute_command_with_tracking`].
+use crate::{api, backend, execute_command_with_tracking, secrets, templating};
+use anyhow::{Context, Result};
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context as RlContext, Editor, Helper};
+use std::borrow::Cow;
+
+/// Backs tab-completion and inline hinting against a snapshot of `user`'s commands, fetched once
+/// when the session starts (the same [`backend::Backend::get_all_user_commands`] call `List`,
+/// `Info` and `Edit` already pull from) rather than on every keystroke.
+struct ReplHelper {
+    commands: Vec<api::Command>,
+}
+
+impl ReplHelper {
+    /// The candidate strings completion/hinting match against: every command's `id` and
+    /// `namespace/name` pair.
+    fn candidates(&self) -> impl Iterator<Item = (String, &api::Command)> {
+        self.commands.iter().flat_map(|cmd| {
+            [
+                (cmd.id.to_string(), cmd),
+                (format!("{}/{}", cmd.namespace, cmd.name), cmd),
+            ]
+        })
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &RlContext<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos].rfind(' ').map(|i| i + 1).unwrap_or(0);
+        let typed = &line[start..pos];
+        if typed.is_empty() {
+            return Ok((start, Vec::new()));
+        }
+
+        let mut pairs: Vec<Pair> = self
+            .candidates()
+            .filter(|(candidate, _)| candidate.starts_with(typed))
+            .map(|(candidate, cmd)| Pair {
+                display: format!("{candidate} :: {}", cmd.command_string),
+                replacement: candidate,
+            })
+            .collect();
+        pairs.sort_by(|a, b| a.replacement.cmp(&b.replacement));
+        pairs.dedup_by(|a, b| a.replacement == b.replacement);
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &RlContext<'_>) -> Option<String> {
+        if pos < line.len() || line.is_empty() {
+            return None;
+        }
+        let (target, rest) = target_and_args(line);
+        if target.is_empty() {
+            return None;
+        }
+
+        let (candidate, cmd) = self
+            .candidates()
+            .filter(|(candidate, _)| candidate.starts_with(target))
+            .min_by_key(|(candidate, _)| candidate.len())?;
+
+        let completion = &candidate[target.len()..];
+        if rest.is_empty() {
+            Some(format!("{completion}  :: {}", cmd.command_string))
+        } else {
+            Some(completion.to_string())
+        }
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(format!("\x1b[90m{hint}\x1b[0m"))
+    }
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Splits an input line into its leading target token (an ID or `namespace/name`) and the
+/// remaining whitespace-separated arguments.
+fn target_and_args(line: &str) -> (&str, &str) {
+    match line.split_once(char::is_whitespace) {
+        Some((target, rest)) => (target, rest.trim_start()),
+        None => (line, ""),
+    }
+}
+
+/// Resolves `target` (an ID or `namespace/name`) against the backend the same way
+/// [`crate::Commands::Recall`] does, bumping the command's usage stats in the process.
+async fn resolve_target(
+    api_client: &dyn backend::Backend,
+    target: &str,
+    user: &str,
+    hostname: &str,
+    cwd: &str,
+) -> Result<api::Command> {
+    if let Ok(command_id) = target.parse::<i32>() {
+        return api_client
+            .execute_command(command_id, user)
+            .await
+            .with_context(|| format!("No command with ID {command_id}"));
+    }
+
+    let (namespace, name) = target
+        .split_once('/')
+        .with_context(|| format!("'{target}' isn't a command ID or a namespace/name pair"))?;
+    api_client
+        .recall_command(namespace, name, user, hostname, cwd)
+        .await
+        .with_context(|| format!("Failed to recall '{target}'"))
+}
+
+/// Resolves and runs one interactive line: `target` through [`resolve_target`], then
+/// [`templating::resolve_arguments`] → [`secrets::resolve_secrets_with`] →
+/// [`execute_command_with_tracking`], the same pipeline [`crate::Commands::Recall`] uses — except
+/// `exit_on_failure` is `false`, since a non-zero exit from one recalled command (e.g. a `grep`
+/// with no matches) should print an error and return to the `hp>` prompt, not kill the whole REPL
+/// process the way it's fine for `Recall`'s one-shot invocation to do.
+#[allow(clippy::too_many_arguments)]
+async fn run_line(
+    api_client: &dyn backend::Backend,
+    telemetry: Option<&crate::telemetry::TelemetryGuard>,
+    settings: &crate::config::Settings,
+    target: &str,
+    args: &[String],
+    user: &str,
+    hostname: &str,
+    cwd: &str,
+) -> Result<()> {
+    let command = resolve_target(api_client, target, user, hostname, cwd).await?;
+
+    let templated_command = templating::resolve_arguments(&command.command_string, args)?;
+    let providers =
+        secrets::providers_for_namespace(&settings.secret_providers, &command.namespace);
+    let resolved_command = secrets::resolve_secrets_with(&providers, &templated_command)?;
+
+    execute_command_with_tracking(
+        api_client,
+        telemetry,
+        settings,
+        &command,
+        user,
+        hostname,
+        cwd,
+        &resolved_command,
+        "interactive",
+        args,
+        false,
+        true,
+    )
+    .await
+    .map(|_exit_code| ())
+}
+
+/// Runs the `hp interactive` REPL: fetches `user`'s commands once, then reads lines with
+/// completion and hinting until `exit`/`quit` or EOF (Ctrl-D).
+pub async fn run(
+    api_client: &dyn backend::Backend,
+    telemetry: Option<&crate::telemetry::TelemetryGuard>,
+    settings: &crate::config::Settings,
+    user: &str,
+    hostname: &str,
+    cwd: &str,
+) -> Result<()> {
+    let commands = api_client
+        .get_all_user_commands(user)
+        .await
+        .context("Failed to load commands for the interactive session")?;
+
+    println!(
+        "hp interactive — {} command(s) loaded. Type a namespace/name or ID, Tab to complete, \
+         Ctrl-D to exit.",
+        commands.len()
+    );
+
+    let mut editor: Editor<ReplHelper, rustyline::history::DefaultHistory> =
+        Editor::new().context("Failed to start the interactive editor")?;
+    editor.set_helper(Some(ReplHelper { commands }));
+
+    loop {
+        let line = match editor.readline("hp> ") {
+            Ok(line) => line,
+            Err(rustyline::error::ReadlineError::Interrupted) => continue,
+            Err(rustyline::error::ReadlineError::Eof) => break,
+            Err(e) => return Err(e).context("Failed to read interactive input"),
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line).ok();
+        if matches!(line, "exit" | "quit") {
+            break;
+        }
+
+        let (target, rest) = target_and_args(line);
+        let args: Vec<String> = rest.split_whitespace().map(str::to_string).collect();
+
+        if let Err(e) = run_line(
+            api_client, telemetry, settings, target, &args, user, hostname, cwd,
+        )
+        .await
+        {
+            eprintln!("Error: {e:#}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,493 @@
+//! An offline [`backend::Backend`](crate::backend::Backend) implementation backed by an embedded
+//! SQLite database, so `hp` keeps working (saving, recalling, executing commands) without a
+//! running `hiproc` server. Persisted under the config directory (see
+//! [`crate::config::config_dir`]) in a `local.db` file by default.
+//!
+//! `rusqlite` calls are blocking, so every method hands the connection off to
+//! `tokio::task::spawn_blocking` rather than holding it across an `.await`.
+use crate::api::{
+    Command, CommandRename, CommandUpdate, ExecutionHistoryCreate, ExecutionRecord, NewCommand,
+    ProjectContextRequest, ProjectContextResponse, RecallByNameRequest, SuggestionsRequest,
+};
+use crate::backend::Backend;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Returns the default local database path: `<config_dir>/local.db`.
+pub fn default_db_path() -> Option<PathBuf> {
+    crate::config::config_dir().map(|dir| dir.join("local.db"))
+}
+
+/// An offline command store backed by a single SQLite file.
+pub struct LocalBackend {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl LocalBackend {
+    /// Opens (creating if necessary) the SQLite database at `path`, running schema migrations.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open local database at {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .context("Failed to run local database schema migrations")?;
+
+        Ok(LocalBackend {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Runs `f` against the underlying connection on a blocking thread.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().unwrap();
+            f(&conn)
+        })
+        .await
+        .context("Local database worker thread panicked")?
+    }
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS commands (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    command_string TEXT NOT NULL,
+    name          TEXT NOT NULL,
+    namespace     TEXT NOT NULL,
+    user          TEXT,
+    cwd           TEXT,
+    hostname      TEXT,
+    scope         TEXT NOT NULL,
+    created_at    TEXT NOT NULL,
+    last_used_at  TEXT,
+    use_count     INTEGER NOT NULL DEFAULT 0,
+    description   TEXT,
+    UNIQUE(namespace, name)
+);
+CREATE TABLE IF NOT EXISTS execution_history (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    command_id      INTEGER NOT NULL,
+    user            TEXT,
+    hostname        TEXT,
+    cwd             TEXT,
+    arguments       TEXT,
+    execution_method TEXT NOT NULL,
+    duration_ms     INTEGER,
+    exit_code       INTEGER,
+    executed_at     TEXT NOT NULL
+);
+";
+
+fn row_to_command(row: &rusqlite::Row) -> rusqlite::Result<Command> {
+    Ok(Command {
+        id: row.get("id")?,
+        command_string: row.get("command_string")?,
+        name: row.get("name")?,
+        namespace: row.get("namespace")?,
+        user: row.get("user")?,
+        cwd: row.get("cwd")?,
+        hostname: row.get("hostname")?,
+        scope: row.get("scope")?,
+        created_at: row.get("created_at")?,
+        last_used_at: row.get("last_used_at")?,
+        use_count: row.get("use_count")?,
+        is_new: false,
+        description: row.get("description")?,
+    })
+}
+
+fn row_to_execution_record(row: &rusqlite::Row) -> rusqlite::Result<ExecutionRecord> {
+    Ok(ExecutionRecord {
+        id: row.get("id")?,
+        command_id: row.get("command_id")?,
+        user: row.get("user")?,
+        hostname: row.get("hostname")?,
+        cwd: row.get("cwd")?,
+        arguments: row.get("arguments")?,
+        execution_method: row.get("execution_method")?,
+        duration_ms: row.get("duration_ms")?,
+        exit_code: row.get("exit_code")?,
+        executed_at: row.get("executed_at")?,
+    })
+}
+
+fn fetch_command_by_id(conn: &Connection, command_id: i32) -> Result<Option<Command>> {
+    conn.query_row(
+        "SELECT * FROM commands WHERE id = ?1",
+        params![command_id],
+        row_to_command,
+    )
+    .optional()
+    .context("Failed to query local database")
+}
+
+#[async_trait]
+impl Backend for LocalBackend {
+    async fn save_command(&self, new_command: NewCommand) -> Result<Command> {
+        self.with_conn(move |conn| {
+            let existing = conn
+                .query_row(
+                    "SELECT * FROM commands WHERE namespace = ?1 AND name = ?2",
+                    params![new_command.namespace, new_command.name],
+                    row_to_command,
+                )
+                .optional()?;
+            if let Some(existing) = existing {
+                return Ok(existing);
+            }
+
+            let now = Utc::now();
+            conn.execute(
+                "INSERT INTO commands (command_string, name, namespace, user, cwd, hostname, scope, created_at, use_count, description)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9)",
+                params![
+                    new_command.command_string,
+                    new_command.name,
+                    new_command.namespace,
+                    new_command.user,
+                    new_command.cwd,
+                    new_command.hostname,
+                    new_command.scope,
+                    now.to_rfc3339(),
+                    new_command.description,
+                ],
+            )?;
+            let id = conn.last_insert_rowid() as i32;
+            let mut saved = fetch_command_by_id(conn, id)?
+                .context("Just-inserted command vanished from the local database")?;
+            saved.is_new = true;
+            Ok(saved)
+        })
+        .await
+    }
+
+    async fn get_commands(
+        &self,
+        query: &str,
+        namespace: Option<&str>,
+        user: Option<&str>,
+        scope: Option<&str>,
+    ) -> Result<Vec<Command>> {
+        let query = query.to_string();
+        let namespace = namespace.map(str::to_string);
+        let user = user.map(str::to_string);
+        let scope = scope.map(str::to_string);
+        self.with_conn(move |conn| {
+            let like = format!("%{}%", query);
+            let mut stmt = conn.prepare(
+                "SELECT * FROM commands
+                 WHERE (command_string LIKE ?1 OR name LIKE ?1)
+                 AND (?2 IS NULL OR namespace = ?2)
+                 AND (?3 IS NULL OR user = ?3)
+                 AND (?4 IS NULL OR scope = ?4)
+                 ORDER BY use_count DESC",
+            )?;
+            let rows = stmt
+                .query_map(params![like, namespace, user, scope], row_to_command)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn recall_command(
+        &self,
+        namespace: &str,
+        name: &str,
+        _user: &str,
+        _hostname: &str,
+        _cwd: &str,
+    ) -> Result<Command> {
+        let namespace = namespace.to_string();
+        let name = name.to_string();
+        self.with_conn(move |conn| {
+            let command = conn
+                .query_row(
+                    "SELECT * FROM commands WHERE namespace = ?1 AND name = ?2",
+                    params![namespace, name],
+                    row_to_command,
+                )
+                .optional()?;
+            match command {
+                Some(command) => Ok(command),
+                None => bail!(
+                    "Command '{}' in namespace '{}' not found in the local database.",
+                    name,
+                    namespace
+                ),
+            }
+        })
+        .await
+    }
+
+    async fn get_namespaces(&self) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT DISTINCT namespace FROM commands ORDER BY namespace")?;
+            let rows = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn delete_command(&self, command_id: i32, _user: &str) -> Result<Command> {
+        self.with_conn(move |conn| {
+            let command = fetch_command_by_id(conn, command_id)?
+                .with_context(|| format!("Command {} not found in the local database.", command_id))?;
+            conn.execute("DELETE FROM commands WHERE id = ?1", params![command_id])?;
+            Ok(command)
+        })
+        .await
+    }
+
+    async fn update_command(
+        &self,
+        command_id: i32,
+        _user: &str,
+        command_update: CommandUpdate,
+    ) -> Result<Command> {
+        self.with_conn(move |conn| {
+            let updated = conn.execute(
+                "UPDATE commands SET command_string = ?1 WHERE id = ?2",
+                params![command_update.command_string, command_id],
+            )?;
+            if updated == 0 {
+                bail!("Command {} not found in the local database.", command_id);
+            }
+            fetch_command_by_id(conn, command_id)?
+                .context("Just-updated command vanished from the local database")
+        })
+        .await
+    }
+
+    async fn get_all_user_commands(&self, user: &str) -> Result<Vec<Command>> {
+        let user = user.to_string();
+        self.with_conn(move |conn| {
+            let mut stmt =
+                conn.prepare("SELECT * FROM commands WHERE user = ?1 ORDER BY namespace, name")?;
+            let rows = stmt
+                .query_map(params![user], row_to_command)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn rename_command(
+        &self,
+        command_id: i32,
+        _user: &str,
+        command_rename: CommandRename,
+    ) -> Result<Command> {
+        self.with_conn(move |conn| {
+            let updated = conn.execute(
+                "UPDATE commands SET name = ?1, namespace = ?2 WHERE id = ?3",
+                params![command_rename.name, command_rename.namespace, command_id],
+            )?;
+            if updated == 0 {
+                bail!("Command {} not found in the local database.", command_id);
+            }
+            fetch_command_by_id(conn, command_id)?
+                .context("Just-renamed command vanished from the local database")
+        })
+        .await
+    }
+
+    async fn execute_command(&self, command_id: i32, _user: &str) -> Result<Command> {
+        self.with_conn(move |conn| {
+            let updated = conn.execute(
+                "UPDATE commands SET use_count = use_count + 1, last_used_at = ?1 WHERE id = ?2",
+                params![Utc::now().to_rfc3339(), command_id],
+            )?;
+            if updated == 0 {
+                bail!(
+                    "Command with ID {} not found in the local database.",
+                    command_id
+                );
+            }
+            fetch_command_by_id(conn, command_id)?
+                .context("Just-executed command vanished from the local database")
+        })
+        .await
+    }
+
+    async fn recall_command_by_name(&self, request: RecallByNameRequest) -> Result<Command> {
+        self.with_conn(move |conn| {
+            let command = match &request.namespace_hint {
+                Some(namespace) => conn
+                    .query_row(
+                        "SELECT * FROM commands WHERE namespace = ?1 AND name = ?2",
+                        params![namespace, request.name],
+                        row_to_command,
+                    )
+                    .optional()?,
+                None => conn
+                    .query_row(
+                        "SELECT * FROM commands WHERE name = ?1 ORDER BY use_count DESC LIMIT 1",
+                        params![request.name],
+                        row_to_command,
+                    )
+                    .optional()?,
+            };
+            match command {
+                Some(command) => Ok(command),
+                None => bail!(
+                    "No command named '{}' found in the local database.",
+                    request.name
+                ),
+            }
+        })
+        .await
+    }
+
+    async fn get_suggestions(&self, request: SuggestionsRequest) -> Result<Vec<Command>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT * FROM commands
+                 WHERE (?1 IS NULL OR user = ?1)
+                 ORDER BY use_count DESC, last_used_at DESC
+                 LIMIT ?2",
+            )?;
+            let rows = stmt
+                .query_map(params![request.user, request.limit], row_to_command)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn detect_project_context(
+        &self,
+        _request: ProjectContextRequest,
+    ) -> Result<ProjectContextResponse> {
+        Ok(ProjectContextResponse {
+            detected_namespace: None,
+            project_type: None,
+            confidence_score: 0,
+            similar_commands: Vec::new(),
+        })
+    }
+
+    async fn get_similar_commands(&self, command_id: i32, limit: Option<i32>) -> Result<Vec<Command>> {
+        let limit = limit.unwrap_or(5);
+        self.with_conn(move |conn| {
+            let namespace: Option<String> = conn
+                .query_row(
+                    "SELECT namespace FROM commands WHERE id = ?1",
+                    params![command_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(namespace) = namespace else {
+                return Ok(Vec::new());
+            };
+
+            let mut stmt = conn.prepare(
+                "SELECT * FROM commands WHERE namespace = ?1 AND id != ?2 ORDER BY use_count DESC LIMIT ?3",
+            )?;
+            let rows = stmt
+                .query_map(params![namespace, command_id, limit], row_to_command)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn get_execution_analytics(&self, user: Option<&str>, days: Option<i32>) -> Result<Value> {
+        let user = user.map(str::to_string);
+        self.with_conn(move |conn| {
+            let cutoff = days
+                .map(|days| (Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339());
+            let total: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM execution_history
+                 WHERE (?1 IS NULL OR user = ?1) AND (?2 IS NULL OR executed_at >= ?2)",
+                params![user, cutoff],
+                |row| row.get(0),
+            )?;
+            Ok(serde_json::json!({ "total_executions": total }))
+        })
+        .await
+    }
+
+    async fn get_execution_history(&self, command_id: i32, limit: i32) -> Result<Vec<ExecutionRecord>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT * FROM execution_history WHERE command_id = ?1 ORDER BY executed_at DESC LIMIT ?2",
+            )?;
+            let rows = stmt
+                .query_map(params![command_id, limit], row_to_execution_record)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn find_commands_by_text(&self, query: &str) -> Result<Vec<Command>> {
+        let query = query.to_string();
+        self.with_conn(move |conn| {
+            let like = format!("%{}%", query);
+            let mut stmt = conn.prepare(
+                "SELECT * FROM commands
+                 WHERE command_string LIKE ?1
+                    OR name LIKE ?1
+                    OR namespace LIKE ?1
+                    OR description LIKE ?1
+                 ORDER BY namespace, use_count DESC",
+            )?;
+            let rows = stmt
+                .query_map(params![like], row_to_command)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    fn run_pre_execute_hooks(&self, _command: &Command) -> Result<()> {
+        // The local backend has no remote-only pre-execute hook configuration to run.
+        Ok(())
+    }
+
+    async fn record_execution(
+        &self,
+        command: &Command,
+        execution: ExecutionHistoryCreate,
+    ) -> Result<Value> {
+        let command_id = command.id;
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO execution_history
+                 (command_id, user, hostname, cwd, arguments, execution_method, duration_ms, exit_code, executed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    command_id,
+                    execution.user,
+                    execution.hostname,
+                    execution.cwd,
+                    execution.arguments,
+                    execution.execution_method,
+                    execution.duration_ms,
+                    execution.exit_code,
+                    Utc::now().to_rfc3339(),
+                ],
+            )?;
+            Ok(serde_json::json!({ "recorded": true }))
+        })
+        .await
+    }
+}
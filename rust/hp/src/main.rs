@@ -4,23 +4,45 @@ use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{Shell, generate};
 use comfy_table::Table;
 use exec;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
 use skim::prelude::*;
 use std::env;
-use std::io::{self, Cursor};
+use std::io;
 
 pub mod api;
 #[cfg(test)]
 mod api_integration_test;
+mod approval;
+pub mod auth;
+mod backend;
+mod batch;
 mod config;
+#[cfg(all(test, feature = "container-tests"))]
+mod container_integration_test;
 mod history;
+mod importer;
+mod interactive;
+mod local;
+mod manifest;
+mod plugins;
 mod secrets;
+mod suggest;
+mod telemetry;
 mod templating;
 #[cfg(test)]
 mod templating_test;
+#[cfg(test)]
+mod tls_integration_test;
+mod vault;
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
-#[clap(subcommand_required = true, arg_required_else_help = true)]
+#[clap(
+    subcommand_required = true,
+    arg_required_else_help = true,
+    disable_help_subcommand = true
+)]
 #[clap(
     after_help = "QUICK WORKFLOWS:\n  hp save \"command\"        Save command with auto-detected name/namespace\n  hp save \"command\" name   Save command with custom name, auto-detect namespace\n  hp do \"command\"          Execute and save command in one step (alias: hp x)\n  hp quick-save name       Save last shell command with custom name\n\nDIRECT EXECUTION:\n  hp <id>                  Execute stored command by ID\n  hp <namespace> <name>    Execute stored command by namespace and name\n\nExamples:\n  hp save \"cargo build\"             # Saves as 'cargo' in current project namespace\n  hp save \"ls -la\" list             # Saves as 'list' with auto-detected namespace\n  hp do git status                  # Executes and saves 'git status' as 'git/status'\n  hp 123                            # Run stored command ID 123\n  hp rust build                     # Run 'build' command from 'rust' namespace"
 )]
@@ -49,6 +71,9 @@ enum Commands {
         namespace: Option<String>,
         #[clap(long, default_value = "personal")]
         scope: String,
+        /// Free-text notes on why this command exists, for later recall by `hp help --find`
+        #[clap(long)]
+        description: Option<String>,
     },
     /// Search for commands
     Search {
@@ -60,6 +85,14 @@ enum Commands {
         #[clap(long)]
         user: Option<String>,
     },
+    /// Show help, or search saved command content for a phrase (unlike `Search`, this isn't
+    /// scoped to a namespace/user/scope and also matches `description`)
+    Help {
+        /// Text to search for across command_string, namespace, name, and description. Omit to
+        /// just print this help.
+        #[clap(long)]
+        find: Option<String>,
+    },
     /// List all namespaces
     Namespaces,
     /// List user's commands with IDs
@@ -106,8 +139,25 @@ enum Commands {
     Delete { command_id: i32 },
     /// Edit a command by ID
     Edit { command_id: i32 },
+    /// Review and approve a non-personal-scope command by ID, so it can run without an
+    /// interactive prompt (or at all, under `require_review`). Re-approving after the command's
+    /// `command_string` changes on the server clears the stale approval.
+    Approve { command_id: i32 },
     /// Generate shell completion scripts
     GenerateCompletions { shell: Shell },
+    /// Runtime dynamic completion: given the current shell words and the cursor's word index,
+    /// print newline-delimited candidates fetched live from the server (namespaces and saved
+    /// command names/IDs). Invoked by the bootstrap script `GenerateCompletions` emits — not
+    /// meant to be run directly.
+    #[clap(hide = true)]
+    Complete {
+        /// Index into `words` of the word currently being completed.
+        #[clap(long)]
+        cursor: usize,
+        /// The full current command line, split into words by the shell (including `hp` itself).
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+        words: Vec<String>,
+    },
     /// Execute a command by ID with optional arguments (also: hp <id>)
     Exec {
         command_id: i32,
@@ -130,6 +180,13 @@ enum Commands {
         #[clap(long)]
         namespace: Option<String>,
     },
+    /// List shell history ranked by frequency, recency, cwd match, and complexity, instead of raw
+    /// file-order recency (see `Settings::rank_weights`)
+    Recent {
+        /// How many ranked commands to show
+        #[clap(long, default_value_t = 10)]
+        count: usize,
+    },
     /// Execute and save a command with smart defaults
     #[clap(alias = "x")]
     Do {
@@ -149,12 +206,60 @@ enum Commands {
         #[clap(required = true, num_args = 1..)]
         command_parts: Vec<String>,
     },
+    /// Manage the encrypted local secret vault backing `{{secret:NAME}}` resolution
+    Secret {
+        #[clap(subcommand)]
+        action: SecretCommand,
+    },
+    /// Bulk-import an entire shell history file as saved procedures
+    Import {
+        /// Path to the history file to import (defaults to the current shell's history file)
+        #[clap(long)]
+        file: Option<String>,
+        /// Namespace to import commands into
+        #[clap(long, default_value = "imported")]
+        namespace: String,
+        /// Number of entries to process per batch
+        #[clap(long, default_value = "100")]
+        batch_size: usize,
+    },
+
+    /// Start an interactive REPL: tab-complete namespace/name pairs and IDs, preview the
+    /// resolved command inline, and press enter to run it
+    Interactive,
+    /// Run a "runbook" of saved commands (IDs or namespace/name pairs) in sequence, or with
+    /// bounded parallelism, and print a summary table
+    Batch {
+        /// Command references to run: IDs or `namespace/name` pairs
+        #[clap(conflicts_with = "file")]
+        refs: Vec<String>,
+        /// Read command references from a file instead, one per line (blank lines and `#`
+        /// comments ignored)
+        #[clap(long, conflicts_with = "refs")]
+        file: Option<String>,
+        /// Keep running remaining entries after one fails, instead of stopping at the first
+        /// failure
+        #[clap(long)]
+        continue_on_error: bool,
+        /// Run up to this many entries concurrently instead of strictly in sequence
+        #[clap(long, default_value = "1")]
+        parallelism: usize,
+    },
 
     /// Recall and execute a command by namespace and name, or execute by ID.
     #[clap(external_subcommand)]
     Recall(Vec<String>),
 }
 
+#[derive(Subcommand, Debug)]
+enum SecretCommand {
+    /// Store a secret value in the encrypted vault, creating the vault on first use
+    Set {
+        /// Name of the secret, matching the `{{NAME}}` placeholder used in saved commands
+        name: String,
+    },
+}
+
 /// Auto-detect a command name from the command string.
 ///
 /// This function extracts a reasonable name from a command string by taking the first word
@@ -188,50 +293,62 @@ fn detect_name_from_command(command_string: &str) -> String {
     }
 }
 
-/// Detect namespace from current directory context.
-///
-/// This function analyzes the current directory to suggest a namespace for the command.
-fn detect_namespace_from_context() -> Option<String> {
-    let cwd = std::env::current_dir().ok()?;
+/// Detect namespace from current directory context, trying ecosystems in `settings`'s configured
+/// [`manifest::ManifestEcosystem`] order. Thin wrapper around [`manifest::detect_namespace_from`]
+/// that reads the process' current directory once, so the core detection logic stays pure over an
+/// injected path.
+fn detect_namespace_from_context(settings: &config::Settings) -> Option<String> {
+    let cwd = manifest::AbsPathBuf::current_dir().ok()?;
+    manifest::detect_namespace_from(cwd.as_abs_path(), &settings.namespace_detectors)
+        .map(|(_root, namespace)| namespace)
+}
 
-    // Check for common project indicators and use directory name with prefix
-    if cwd.join("package.json").exists() {
-        return cwd
-            .file_name()
-            .and_then(|name| name.to_str())
-            .map(|s| s.to_string());
-    }
-    if cwd.join("Cargo.toml").exists() {
-        return cwd
-            .file_name()
-            .and_then(|name| name.to_str())
-            .map(|s| s.to_string());
-    }
-    if cwd.join("pyproject.toml").exists() || cwd.join("setup.py").exists() {
-        return cwd
-            .file_name()
-            .and_then(|name| name.to_str())
-            .map(|s| s.to_string());
-    }
-    if cwd.join(".git").exists() {
-        return cwd
-            .file_name()
-            .and_then(|name| name.to_str())
-            .map(|s| s.to_string());
-    }
+/// Detects a namespace for the current directory, preferring any loaded plugin implementing the
+/// `detect_namespace` hook (see [`plugins`]) over the built-in heuristic in
+/// [`detect_namespace_from_context`].
+fn detect_namespace(settings: &config::Settings) -> Option<String> {
+    let cwd = std::env::current_dir().ok()?;
+    let cwd = cwd.to_str()?;
+    plugins::plugin_manager()
+        .detect_namespace(cwd)
+        .or_else(|| detect_namespace_from_context(settings))
+}
 
-    // Fallback to directory name
-    cwd.file_name()
-        .and_then(|name| name.to_str())
-        .map(|s| s.to_string())
+/// Validates a namespace the user typed directly on the command line (as opposed to one
+/// auto-detected from context, which already goes through sanitization in
+/// [`manifest::detect_namespace_from`]), bailing with a clear message if it's not a valid
+/// namespace. Shared by every subcommand that accepts a raw `--namespace`/positional namespace
+/// argument: `Save`, `QuickSave`, `Rename`, `Do`, and `Import`.
+fn require_valid_namespace(namespace: &str) -> Result<()> {
+    manifest::validate_namespace_name(namespace)
+        .map_err(|reason| anyhow::anyhow!("'{namespace}' isn't a valid namespace: {reason}"))
 }
 
 /// Execute a command with history tracking.
 ///
 /// This function handles command execution and creates execution history records
-/// for analytics and tracking purposes.
+/// for analytics and tracking purposes. Returns the child's exit code; when `exit_on_failure` is
+/// set, a non-zero exit terminates the whole `hp` process with that code instead of returning (the
+/// behavior every single-command subcommand wants). `hp batch` passes `false` so it can keep going
+/// and report every entry's outcome in its summary table instead of dying on the first failure.
+///
+/// Before anything runs, a `command.scope` other than `"personal"` must clear the approval gate
+/// (see [`approval::ApprovalStore`]): already-approved commands (by ID and current
+/// `command_string` hash) proceed silently; unapproved ones either prompt for an interactive
+/// review (recording the approval on "yes") or fail outright and point at `hp approve`, either
+/// because `settings.require_review` is set or because `interactive_approval` is `false` (`hp
+/// batch` passes `false` when running with `--parallelism > 1`, since concurrent entries
+/// prompting on the same stdin at once would just garble each other).
+///
+/// The child process itself runs on a blocking-pool thread via `tokio::task::spawn_blocking`,
+/// same as [`local::LocalBackend`]'s SQLite calls — `std::process::Command::spawn`/`wait` are
+/// blocking calls that would otherwise stall every other task on the worker thread they land on,
+/// which is exactly what silently serialized `hp batch --parallelism N`'s "concurrent" entries.
+#[allow(clippy::too_many_arguments)]
 async fn execute_command_with_tracking(
-    api_client: &api::ApiClient,
+    api_client: &dyn backend::Backend,
+    telemetry: Option<&telemetry::TelemetryGuard>,
+    settings: &config::Settings,
     command: &api::Command,
     user: &str,
     hostname: &str,
@@ -239,27 +356,72 @@ async fn execute_command_with_tracking(
     resolved_command: &str,
     execution_method: &str,
     args: &[String],
-) -> Result<()> {
+    exit_on_failure: bool,
+    interactive_approval: bool,
+) -> Result<i32> {
     use std::process::{Command, Stdio};
     use std::time::Instant;
 
+    if command.scope != "personal" {
+        let mut store = approval::ApprovalStore::load()
+            .context("Failed to load the command approval store")?;
+        if !store.is_approved(command.id, &command.command_string) {
+            if settings.require_review || !interactive_approval {
+                anyhow::bail!(
+                    "Command {} has shared scope '{}' and has not been reviewed; run `hp approve {}` first",
+                    command.id, command.scope, command.id
+                );
+            }
+
+            println!(
+                "Command {} has shared scope '{}' and has not been reviewed:",
+                command.id, command.scope
+            );
+            println!("  {}", command.command_string);
+            print!("Run it and record this approval? [y/N] ");
+            io::Write::flush(&mut io::stdout())?;
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+                anyhow::bail!("Execution cancelled: command {} was not approved", command.id);
+            }
+            store.approve(command.id, &command.command_string)?;
+        }
+    }
+
+    api_client
+        .run_pre_execute_hooks(command)
+        .context("Pre-execute hook aborted the command")?;
+
+    // Give any loaded plugin implementing `transform_command` a chance to rewrite the command
+    // before it runs; fall back to the already-resolved command if none does.
+    let resolved_command = plugins::plugin_manager()
+        .transform_command(resolved_command)
+        .unwrap_or_else(|| resolved_command.to_string());
+    let resolved_command = resolved_command.to_string();
+
     println!("Executing command {}: {}", command.id, resolved_command);
 
     let start_time = Instant::now();
 
-    // Execute the command and wait for completion to get timing and exit code
-    let mut child = Command::new("sh")
-        .arg("-c")
-        .arg(resolved_command)
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .spawn()
-        .context("Failed to spawn command")?;
-
-    let exit_status = child.wait().context("Failed to wait for command")?;
-    let duration = start_time.elapsed();
+    // Spawn and wait for the child on a blocking-pool thread: both calls are blocking and would
+    // otherwise stall the async worker thread they land on (see this function's doc comment).
+    let exit_status = tokio::task::spawn_blocking(move || {
+        Command::new("sh")
+            .arg("-c")
+            .arg(&resolved_command)
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .context("Failed to spawn command")?
+            .wait()
+            .context("Failed to wait for command")
+    })
+    .await
+    .context("Command execution thread panicked")??;
 
+    let duration = start_time.elapsed();
     let exit_code = exit_status.code().unwrap_or(-1);
 
     // Create execution history record with timing and exit code data
@@ -279,37 +441,551 @@ async fn execute_command_with_tracking(
     };
 
     // Track execution in background - don't fail if this fails
-    if let Err(e) = api_client.create_execution_record(execution_record).await {
+    if let Err(e) = api_client.record_execution(command, execution_record).await {
         eprintln!("Warning: Failed to track execution: {}", e);
     }
 
+    telemetry::record_execution(
+        telemetry,
+        command.id,
+        &command.namespace,
+        &command.name,
+        user,
+        hostname,
+        cwd,
+        execution_method,
+        exit_code,
+        duration,
+    );
+
     // Exit with the same code as the executed command
-    if !exit_status.success() {
+    if !exit_status.success() && exit_on_failure {
         std::process::exit(exit_code);
     }
 
+    Ok(exit_code)
+}
+
+/// Drains a shell history file through its [`importer::Importer`] in batches, offering to
+/// register each unique command as a hiproc procedure.
+async fn run_import(
+    api_client: &dyn backend::Backend,
+    user: &str,
+    file: Option<String>,
+    namespace: &str,
+    batch_size: usize,
+) -> Result<()> {
+    use crate::importer::Importer;
+    use std::collections::HashSet;
+    use std::fs::File;
+
+    let shell_type = history::ShellType::detect();
+    let path = match file {
+        Some(f) => std::path::PathBuf::from(f),
+        None => shell_type
+            .history_file_path()
+            .context("Could not determine a history file path for the current shell; pass --file")?,
+    };
+
+    let source = File::open(&path)
+        .with_context(|| format!("Failed to open history file at {}", path.display()))?;
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut imported = 0usize;
+    let mut skipped = 0usize;
+
+    macro_rules! drain {
+        ($importer:expr, $total_label:expr) => {{
+            let mut importer = $importer;
+            println!("Found ~{} entries to import from {}", importer.size_hint_total(), path.display());
+            let mut batch = Vec::with_capacity(batch_size);
+            loop {
+                batch.clear();
+                for _ in 0..batch_size {
+                    match importer.next() {
+                        Some(Ok(entry)) => batch.push(entry),
+                        Some(Err(e)) => eprintln!("Warning: skipping unreadable entry: {}", e),
+                        None => break,
+                    }
+                }
+                if batch.is_empty() {
+                    break;
+                }
+                for entry in &batch {
+                    if !seen.insert(entry.command.clone()) {
+                        skipped += 1;
+                        continue;
+                    }
+                    let name = detect_name_from_command(&entry.command);
+                    let new_command = api::NewCommand {
+                        command_string: entry.command.clone(),
+                        name,
+                        namespace: namespace.to_string(),
+                        user: Some(user.to_string()),
+                        cwd: None,
+                        hostname: None,
+                        scope: "personal".to_string(),
+                        description: None,
+                    };
+                    match api_client.save_command(new_command).await {
+                        Ok(cmd) if cmd.is_new => imported += 1,
+                        Ok(_) => skipped += 1,
+                        Err(e) => eprintln!("Warning: failed to import '{}': {}", entry.command, e),
+                    }
+                }
+            }
+        }};
+    }
+
+    match shell_type {
+        history::ShellType::Bash => drain!(importer::BashImporter::new(source)?, "bash"),
+        history::ShellType::Zsh => drain!(importer::ZshImporter::new(source)?, "zsh"),
+        history::ShellType::Fish => drain!(importer::FishImporter::new(source)?, "fish"),
+        history::ShellType::Unknown => {
+            anyhow::bail!("Unknown shell; pass --file along with a known $SHELL to select a parser")
+        }
+    }
+
+    println!(
+        "Imported {} new command(s) into namespace '{}' ({} duplicate/skipped)",
+        imported, namespace, skipped
+    );
+
     Ok(())
 }
 
+/// Resolves `hp complete --cursor <n> -- <words>` into newline-ready completion candidates,
+/// fetched live from the server since namespaces and saved command names/IDs don't exist in the
+/// static arg grammar. `words` is the full current command line including `hp` itself; `cursor`
+/// is the index of the word being completed.
+async fn complete_words(
+    api_client: &dyn backend::Backend,
+    user: &str,
+    words: &[String],
+    cursor: usize,
+) -> Result<Vec<String>> {
+    let partial = words.get(cursor).map(String::as_str).unwrap_or("");
+    let prev_word = if cursor > 0 {
+        words.get(cursor - 1).map(String::as_str)
+    } else {
+        None
+    };
+
+    // A value for `--namespace` is always a namespace, regardless of which subcommand it's on.
+    if prev_word == Some("--namespace") {
+        return complete_namespaces(api_client, partial).await;
+    }
+
+    // `hp info|exec|similar|rename|delete|edit <id>`: the first positional is a command ID.
+    if cursor == 2 {
+        if let Some(sub) = words.get(1) {
+            if matches!(
+                sub.as_str(),
+                "info" | "exec" | "similar" | "rename" | "delete" | "edit"
+            ) {
+                return complete_command_ids(api_client, user, partial).await;
+            }
+        }
+    }
+
+    // `hp <id>` or `hp <namespace> <name>`, or a subcommand name (`hp save`, `hp find`, ...): the
+    // bare word right after `hp` can be any of the three. The dynamic completion hook registers
+    // over the same shell completion spec the static `clap_complete::generate` script already
+    // wrote, so subcommand names have to be offered here too or they silently stop completing.
+    if cursor == 1 {
+        let mut candidates = complete_subcommands(partial);
+        candidates.extend(complete_namespaces(api_client, partial).await?);
+        candidates.extend(complete_command_ids(api_client, user, partial).await?);
+        return Ok(candidates);
+    }
+
+    // `hp <namespace> <name>`: the second bare word completes names within that namespace.
+    if cursor == 2 {
+        if let Some(namespace) = words.get(1) {
+            return complete_command_names(api_client, user, namespace, partial).await;
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/// Clap subcommand names (`save`, `find`, `exec`, ...) whose name starts with `partial`.
+fn complete_subcommands(partial: &str) -> Vec<String> {
+    Cli::command()
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .filter(|name| name.starts_with(partial))
+        .collect()
+}
+
+/// Namespaces (from `get_namespaces`) whose name starts with `partial`.
+async fn complete_namespaces(api_client: &dyn backend::Backend, partial: &str) -> Result<Vec<String>> {
+    let namespaces = api_client.get_namespaces().await?;
+    Ok(namespaces
+        .into_iter()
+        .filter(|ns| ns.starts_with(partial))
+        .collect())
+}
+
+/// IDs of `user`'s saved commands (from `get_all_user_commands`) whose string form starts with
+/// `partial`.
+async fn complete_command_ids(
+    api_client: &dyn backend::Backend,
+    user: &str,
+    partial: &str,
+) -> Result<Vec<String>> {
+    let commands = api_client.get_all_user_commands(user).await?;
+    Ok(commands
+        .into_iter()
+        .map(|cmd| cmd.id.to_string())
+        .filter(|id| id.starts_with(partial))
+        .collect())
+}
+
+/// Names of `user`'s saved commands within `namespace` that start with `partial`.
+async fn complete_command_names(
+    api_client: &dyn backend::Backend,
+    user: &str,
+    namespace: &str,
+    partial: &str,
+) -> Result<Vec<String>> {
+    let commands = api_client.get_all_user_commands(user).await?;
+    Ok(commands
+        .into_iter()
+        .filter(|cmd| cmd.namespace == namespace && cmd.name.starts_with(partial))
+        .map(|cmd| cmd.name)
+        .collect())
+}
+
+/// On a `Run`/`Recall` miss, fetches `user`'s full command list and prints the top 3 closest
+/// matches to `typed` (see [`suggest::did_you_mean`]), if any are within its distance threshold.
+/// Errors fetching the list are swallowed — this only enriches the error already being reported,
+/// it shouldn't itself mask or replace it.
+async fn print_did_you_mean(api_client: &dyn backend::Backend, user: &str, typed: &str) {
+    let Ok(commands) = api_client.get_all_user_commands(user).await else {
+        return;
+    };
+    let suggestions = suggest::did_you_mean(typed, &commands, 3);
+    if suggestions.is_empty() {
+        return;
+    }
+
+    eprintln!("Did you mean:");
+    for suggestion in suggestions {
+        eprintln!(
+            "  ID:{:<4} {}/{}",
+            suggestion.command.id, suggestion.command.namespace, suggestion.command.name
+        );
+    }
+}
+
+/// Prints any extra suggestions plugins implementing the `suggest` hook (see [`plugins`]) offer
+/// for `cwd`, merged alongside (not replacing) the server's own ranked suggestions.
+fn print_plugin_suggestions(cwd: &str) {
+    let suggestions = plugins::plugin_manager().suggest(cwd, "");
+    if suggestions.is_empty() {
+        return;
+    }
+    println!("Plugin suggestions:");
+    for suggestion in suggestions {
+        println!("  {suggestion}");
+    }
+}
+
+/// Formats a single command as one `hp find` list row: `ID:<id> <namespace> <name> <user>
+/// <scope> <cwd> :: <command_string>`.
+fn format_find_row(cmd: &api::Command) -> String {
+    format!(
+        "ID:{:<4} {:<15} {:<15} {:<10} {:<10} {:<25} :: {}",
+        cmd.id,
+        cmd.namespace,
+        cmd.name,
+        cmd.user.as_deref().unwrap_or(""),
+        cmd.scope,
+        cmd.cwd.as_deref().unwrap_or(""),
+        cmd.command_string
+    )
+}
+
 fn format_find_output(commands: &[api::Command]) -> String {
     commands
         .iter()
-        .map(|cmd| {
-            format!(
-                "ID:{:<4} {:<15} {:<15} {:<10} {:<10} {:<25} :: {}",
-                cmd.id,
-                cmd.namespace,
-                cmd.name,
-                cmd.user.as_deref().unwrap_or(""),
-                cmd.scope,
-                cmd.cwd.as_deref().unwrap_or(""),
-                cmd.command_string
-            )
-        })
+        .map(format_find_row)
         .collect::<Vec<_>>()
         .join("\n")
 }
 
+/// Fuzzy-matches `query` against `cmd`'s `command_string`, `namespace`, `name`, and
+/// `description`, returning the best-scoring field as `(score, snippet)` with the matched
+/// characters highlighted, or `None` if `query` doesn't match any of them. Backs
+/// `hp help --find`.
+fn best_text_match(matcher: &SkimMatcherV2, query: &str, cmd: &api::Command) -> Option<(i64, String)> {
+    [
+        Some(cmd.command_string.as_str()),
+        Some(cmd.namespace.as_str()),
+        Some(cmd.name.as_str()),
+        cmd.description.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|field| {
+        let (score, indices) = matcher.fuzzy_indices(field, query)?;
+        Some((score, highlight_matches(field, &indices)))
+    })
+    .max_by_key(|(score, _)| *score)
+}
+
+/// Wraps the characters at `indices` in bold yellow ANSI escapes so matched snippets stand out
+/// in `hp help --find` output.
+fn highlight_matches(text: &str, indices: &[usize]) -> String {
+    let mut highlighted = String::new();
+    for (i, ch) in text.chars().enumerate() {
+        if indices.contains(&i) {
+            highlighted.push_str("\x1b[1;33m");
+            highlighted.push(ch);
+            highlighted.push_str("\x1b[0m");
+        } else {
+            highlighted.push(ch);
+        }
+    }
+    highlighted
+}
+
+/// How many recent execution history records to show in a `Find` preview pane.
+const FIND_PREVIEW_HISTORY_LIMIT: i32 = 5;
+
+/// Bounds how long a `Find` preview pane will wait on a backend call before giving up and
+/// showing an error instead of hanging the whole interactive session.
+const FIND_PREVIEW_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// A `Commands::Find` row. Carries the full `Command` (for the list line and the preview header)
+/// plus a shared handle to the backend so `preview()` can fetch recent execution history for the
+/// highlighted row on demand, and a cache so re-highlighting the same row doesn't refetch it.
+struct FindItem {
+    command: api::Command,
+    backend: Arc<dyn backend::Backend>,
+    history_cache: Arc<std::sync::Mutex<std::collections::HashMap<i32, String>>>,
+}
+
+impl FindItem {
+    /// Runs `backend.get_execution_history` to completion on a dedicated thread with its own
+    /// single-threaded Tokio runtime, bounded by [`FIND_PREVIEW_TIMEOUT`]. `preview()` is a
+    /// synchronous callback from skim's render loop, so it can't simply `.await` the backend.
+    fn fetch_history(&self) -> Result<Vec<api::ExecutionRecord>> {
+        let backend = Arc::clone(&self.backend);
+        let command_id = self.command.id;
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let result = (|| -> Result<Vec<api::ExecutionRecord>> {
+                let rt = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .context("Failed to start a runtime for the preview pane")?;
+                rt.block_on(backend.get_execution_history(command_id, FIND_PREVIEW_HISTORY_LIMIT))
+            })();
+            let _ = tx.send(result);
+        });
+
+        rx.recv_timeout(FIND_PREVIEW_TIMEOUT)
+            .context("Timed out fetching execution history for the preview pane")?
+    }
+
+    /// Renders the full command details plus recent history, coloring exit codes red (nonzero)
+    /// or green (success) so failing runs stand out at a glance.
+    fn render_preview(&self) -> String {
+        let cmd = &self.command;
+        let mut lines = vec![
+            format!("Command:    {}", cmd.command_string),
+            format!("ID:         {}", cmd.id),
+            format!("Namespace:  {}", cmd.namespace),
+            format!("Name:       {}", cmd.name),
+            format!("Scope:      {}", cmd.scope),
+            format!("User:       {}", cmd.user.as_deref().unwrap_or("-")),
+            format!("Cwd:        {}", cmd.cwd.as_deref().unwrap_or("-")),
+            format!("Use count:  {}", cmd.use_count),
+            format!("Created:    {}", cmd.created_at),
+            format!(
+                "Last used:  {}",
+                cmd.last_used_at
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "-".to_string())
+            ),
+            String::new(),
+            "Recent executions:".to_string(),
+        ];
+
+        match self.fetch_history() {
+            Ok(history) if history.is_empty() => lines.push("  (none recorded)".to_string()),
+            Ok(history) => {
+                for record in history {
+                    let (color, exit_label) = match record.exit_code {
+                        Some(0) => ("\x1b[32m", "0".to_string()),
+                        Some(code) => ("\x1b[31m", code.to_string()),
+                        None => ("\x1b[33m", "?".to_string()),
+                    };
+                    lines.push(format!(
+                        "  {} {}exit {}\x1b[0m  {}ms  {}",
+                        record.executed_at,
+                        color,
+                        exit_label,
+                        record.duration_ms.map(|d| d.to_string()).unwrap_or_else(|| "?".to_string()),
+                        record.arguments.as_deref().unwrap_or(""),
+                    ));
+                }
+            }
+            Err(e) => lines.push(format!("  (failed to load: {e:#})")),
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl SkimItem for FindItem {
+    fn text(&self) -> Cow<str> {
+        Cow::Owned(format_find_row(&self.command))
+    }
+
+    fn output(&self) -> Cow<str> {
+        self.text()
+    }
+
+    fn preview(&self, _context: PreviewContext) -> ItemPreview {
+        let cache_hit = self
+            .history_cache
+            .lock()
+            .unwrap()
+            .get(&self.command.id)
+            .cloned();
+        let rendered = match cache_hit {
+            Some(rendered) => rendered,
+            None => {
+                let rendered = self.render_preview();
+                self.history_cache
+                    .lock()
+                    .unwrap()
+                    .insert(self.command.id, rendered.clone());
+                rendered
+            }
+        };
+        ItemPreview::AnsiText(rendered)
+    }
+}
+
+/// Prints the shell snippet that wires up runtime dynamic completion on top of the static
+/// completion script `clap_complete::generate` already wrote to stdout. Each snippet registers a
+/// completion function that shells out to `hp complete --cursor <n> -- <words>` and feeds the
+/// newline-delimited candidates back into the shell's completion engine, so namespaces and saved
+/// command names/IDs (which only the server knows about) complete like any other argument.
+fn print_dynamic_completion_hook(shell: Shell, bin_name: &str) {
+    match shell {
+        Shell::Bash => println!(
+            r#"
+_{bin_name}_dynamic_complete() {{
+    local cur candidates
+    cur="${{COMP_WORDS[COMP_CWORD]}}"
+    candidates=$({bin_name} complete --cursor "$COMP_CWORD" -- "${{COMP_WORDS[@]}}")
+    COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
+}}
+complete -F _{bin_name}_dynamic_complete {bin_name}
+"#
+        ),
+        Shell::Zsh => println!(
+            r#"
+_{bin_name}_dynamic_complete() {{
+    local -a candidates
+    candidates=(${{(f)"$({bin_name} complete --cursor $((CURRENT - 1)) -- ${{words[@]}})"}})
+    compadd -a candidates
+}}
+compdef _{bin_name}_dynamic_complete {bin_name}
+"#
+        ),
+        Shell::Fish => println!(
+            r#"
+function __{bin_name}_dynamic_complete
+    set -l words (commandline -opc)
+    {bin_name} complete --cursor (count $words) -- $words
+end
+complete -c {bin_name} -f -a '(__{bin_name}_dynamic_complete)'
+"#
+        ),
+        // Elvish/PowerShell (and any future `Shell` variant) get the static script only; we
+        // haven't needed dynamic completion support for them yet.
+        _ => {}
+    }
+}
+
+/// Handles `hp secret <action>`, unsealing or creating the vault as needed.
+fn run_secret_command(action: SecretCommand) -> Result<()> {
+    match action {
+        SecretCommand::Set { name } => {
+            let value = rpassword::prompt_password(format!("Enter value for secret '{}': ", name))
+                .context("Failed to read secret value")?;
+
+            let mut vault = if vault::SecretVault::exists()? {
+                let passphrase = rpassword::prompt_password("Enter vault master passphrase: ")
+                    .context("Failed to read vault passphrase")?;
+                vault::SecretVault::unlock(&passphrase)?
+            } else {
+                println!("No secret vault found; creating one.");
+                let passphrase =
+                    rpassword::prompt_password("Create a master passphrase for the secret vault: ")
+                        .context("Failed to read new vault passphrase")?;
+                vault::SecretVault::create(&passphrase)?
+            };
+
+            vault.set(&name, &value)?;
+            println!("Saved secret '{}' to the vault.", name);
+            Ok(())
+        }
+    }
+}
+
+/// Builds the `ApiClient` used for the `remote` backend: auth from the environment, TLS
+/// configured from `settings.tls` (if any of its fields are set), and an audit-log post-execute
+/// hook registered when `settings.audit_log_path` is set — one JSON line per executed command,
+/// independent of the server's own execution-history table.
+fn build_remote_api_client(settings: &config::Settings) -> Result<api::ApiClient> {
+    let mut builder =
+        api::ApiClient::builder(settings.server_url.clone()).auth(auth::Auth::from_env());
+
+    let tls = &settings.tls;
+    if tls.ca_cert_path.is_some() || tls.client_identity_path.is_some() || tls.accept_invalid_hostnames {
+        let mut tls_config = api::TlsConfig::new();
+        if let Some(path) = &tls.ca_cert_path {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("Failed to read TLS CA cert at {}", path.display()))?;
+            tls_config = tls_config.with_ca_cert_pem(pem);
+        }
+        if let Some(path) = &tls.client_identity_path {
+            let pem = std::fs::read(path).with_context(|| {
+                format!("Failed to read TLS client identity at {}", path.display())
+            })?;
+            tls_config = tls_config.with_client_identity_pem(pem);
+        }
+        tls_config = tls_config.accept_invalid_hostnames(tls.accept_invalid_hostnames);
+        builder = builder.tls(tls_config);
+    }
+
+    if let Some(audit_log_path) = settings.audit_log_path.clone() {
+        builder = builder.on_post_execute(move |command, execution| {
+            let line = serde_json::json!({
+                "command_id": command.id,
+                "command_string": command.command_string,
+                "execution": execution,
+            });
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&audit_log_path)
+                .with_context(|| format!("Failed to open audit log at {}", audit_log_path.display()))?;
+            io::Write::write_all(&mut file, format!("{line}\n").as_bytes())
+                .context("Failed to write audit log entry")
+        });
+    }
+
+    builder.build()
+}
+
 #[tokio::main(flavor = "multi_thread", worker_threads = 1)]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -318,12 +994,40 @@ async fn main() -> Result<()> {
     if let Commands::GenerateCompletions { shell } = cli.command {
         let mut cmd = Cli::command();
         let name = cmd.get_name().to_string();
-        generate(shell, &mut cmd, name, &mut io::stdout());
+        generate(shell, &mut cmd, name.clone(), &mut io::stdout());
+        print_dynamic_completion_hook(shell, &name);
+        return Ok(());
+    }
+
+    // Secret vault management is local-only and doesn't need the API client either.
+    if let Commands::Secret { action } = cli.command {
+        return run_secret_command(action);
+    }
+
+    // `hp help` with no `--find` just prints the usual top-level help and doesn't need the API
+    // client; `hp help --find <text>` does, and is handled below alongside the other commands.
+    if let Commands::Help { find: None } = &cli.command {
+        Cli::command().print_help()?;
+        println!();
         return Ok(());
     }
 
     let settings = config::Settings::new().context("Failed to read configuration")?;
-    let api_client = api::ApiClient::new(settings.server_url);
+    let telemetry_guard =
+        telemetry::init(&settings.telemetry).context("Failed to initialize telemetry")?;
+    let telemetry_guard = telemetry_guard.as_ref();
+    let api_client: Arc<dyn backend::Backend> = match settings.backend {
+        config::BackendKind::Remote => {
+            Arc::new(build_remote_api_client(&settings).context("Failed to build API client")?)
+        }
+        config::BackendKind::Local => {
+            let db_path = settings
+                .local_db_path
+                .or_else(local::default_db_path)
+                .context("Could not determine a path for the local database")?;
+            Arc::new(local::LocalBackend::open(&db_path).context("Failed to open local database")?)
+        }
+    };
     let user = whoami::username();
 
     match cli.command {
@@ -339,19 +1043,27 @@ async fn main() -> Result<()> {
                 return Ok(());
             }
 
-            let item_text = format_find_output(&commands);
-
             let options = SkimOptionsBuilder::default()
                 .height(Some("50%"))
                 .multi(false)
+                .preview(Some(""))
                 .header(Some("ID    Namespace        Name             User       Scope      Directory                 :: Command"))
                 .build()
                 .unwrap();
 
-            let item_reader = SkimItemReader::default();
-            let items = item_reader.of_bufread(Cursor::new(item_text));
+            let history_cache = Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+            let (tx_item, rx_item) = unbounded();
+            for command in commands {
+                let item = FindItem {
+                    command,
+                    backend: Arc::clone(&api_client),
+                    history_cache: Arc::clone(&history_cache),
+                };
+                tx_item.send(Arc::new(item) as Arc<dyn SkimItem>)?;
+            }
+            drop(tx_item);
 
-            let selected_items = Skim::run_with(&options, Some(items))
+            let selected_items = Skim::run_with(&options, Some(rx_item))
                 .map(|out| out.selected_items)
                 .unwrap_or_else(Vec::new);
 
@@ -365,8 +1077,14 @@ async fn main() -> Result<()> {
                             // Fetch the command to get full details for tracking
                             if let Ok(command) = api_client.execute_command(command_id, &user).await
                             {
-                                let resolved_command =
-                                    secrets::resolve_secrets(command_string.trim())?;
+                                let providers = secrets::providers_for_namespace(
+                                    &settings.secret_providers,
+                                    &command.namespace,
+                                );
+                                let resolved_command = secrets::resolve_secrets_with(
+                                    &providers,
+                                    command_string.trim(),
+                                )?;
 
                                 let cwd = env::current_dir()?
                                     .to_str()
@@ -378,7 +1096,9 @@ async fn main() -> Result<()> {
                                     .to_string();
 
                                 execute_command_with_tracking(
-                                    &api_client,
+                                    api_client.as_ref(),
+                                    telemetry_guard,
+                                    &settings,
                                     &command,
                                     &user,
                                     &hostname,
@@ -386,6 +1106,8 @@ async fn main() -> Result<()> {
                                     &resolved_command,
                                     "find",
                                     &[],
+                                    true,
+                                    true,
                                 )
                                 .await?;
                                 return Ok(());
@@ -408,6 +1130,7 @@ async fn main() -> Result<()> {
             name,
             namespace,
             scope,
+            description,
         } => {
             let cwd = env::current_dir()?
                 .to_str()
@@ -421,9 +1144,15 @@ async fn main() -> Result<()> {
             // Auto-detect name if not provided
             let final_name = name.unwrap_or_else(|| detect_name_from_command(&command_string));
 
+            // A user-supplied namespace skips auto-detection (and the sanitization it applies to
+            // manifest-derived names), so validate it explicitly here.
+            if let Some(namespace) = &namespace {
+                require_valid_namespace(namespace)?;
+            }
+
             // Auto-detect namespace if not provided
             let final_namespace = namespace.unwrap_or_else(|| {
-                detect_namespace_from_context().unwrap_or_else(|| "ad-hoc".to_string())
+                detect_namespace(&settings).unwrap_or_else(|| "ad-hoc".to_string())
             });
 
             let new_command = api::NewCommand {
@@ -434,6 +1163,7 @@ async fn main() -> Result<()> {
                 cwd: Some(cwd),
                 hostname: Some(hostname),
                 scope,
+                description,
             };
             let cmd = api_client
                 .save_command(new_command)
@@ -500,6 +1230,47 @@ async fn main() -> Result<()> {
             }
             println!("{table}");
         }
+        // find=None is handled before the API client is initialized.
+        Commands::Help { find: None } => unreachable!(),
+        Commands::Help { find: Some(query) } => {
+            let commands = api_client
+                .find_commands_by_text(&query)
+                .await
+                .context("Failed to search command content")?;
+
+            let matcher = SkimMatcherV2::default();
+            let mut matches: Vec<(i64, api::Command, String)> = commands
+                .into_iter()
+                .filter_map(|cmd| {
+                    let (score, snippet) = best_text_match(&matcher, &query, &cmd)?;
+                    Some((score, cmd, snippet))
+                })
+                .collect();
+
+            if matches.is_empty() {
+                println!("No saved commands match '{}'.", query);
+                return Ok(());
+            }
+
+            // Highest-scoring match first within each namespace, then group namespaces together.
+            matches.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+            matches.sort_by(|a, b| a.1.namespace.cmp(&b.1.namespace));
+
+            let mut last_namespace: Option<&str> = None;
+            for (_, cmd, snippet) in &matches {
+                if last_namespace != Some(cmd.namespace.as_str()) {
+                    println!("\n{}:", cmd.namespace);
+                    last_namespace = Some(cmd.namespace.as_str());
+                }
+                println!("  ID:{:<4} {:<15} :: {}", cmd.id, cmd.name, snippet);
+            }
+        }
+        Commands::Complete { cursor, words } => {
+            let candidates = complete_words(api_client.as_ref(), &user, &words, cursor).await?;
+            for candidate in candidates {
+                println!("{candidate}");
+            }
+        }
         Commands::Namespaces => {
             let namespaces = api_client
                 .get_namespaces()
@@ -673,6 +1444,7 @@ async fn main() -> Result<()> {
                     }
                     println!("{table}");
                 }
+                print_plugin_suggestions(&cwd);
             }
         }
         Commands::Suggest {
@@ -691,7 +1463,7 @@ async fn main() -> Result<()> {
             let suggestions_request = api::SuggestionsRequest {
                 user: Some(user.clone()),
                 hostname: Some(hostname),
-                cwd: Some(cwd),
+                cwd: Some(cwd.clone()),
                 project_type,
                 limit: limit.unwrap_or(5),
             };
@@ -730,6 +1502,7 @@ async fn main() -> Result<()> {
                 }
                 println!("{table}");
             }
+            print_plugin_suggestions(&cwd);
         }
         Commands::Similar { command_id, limit } => {
             let similar_commands = api_client
@@ -839,6 +1612,8 @@ async fn main() -> Result<()> {
             new_namespace,
             new_name,
         } => {
+            require_valid_namespace(&new_namespace)?;
+
             let rename_payload = api::CommandRename {
                 name: new_name,
                 namespace: new_namespace,
@@ -882,6 +1657,32 @@ async fn main() -> Result<()> {
                 command_id, deleted_command.name, deleted_command.namespace
             );
         }
+        Commands::Approve { command_id } => {
+            let command = api_client
+                .execute_command(command_id, &user)
+                .await
+                .context("Failed to look up command to approve")?;
+
+            println!(
+                "About to approve:\n  ID:         {}\n  Namespace:  {}\n  Name:       {}\n  \
+                 Scope:      {}\n  Command:    {}",
+                command.id, command.namespace, command.name, command.scope, command.command_string
+            );
+            print!("Approve this command for execution? [y/N] ");
+            io::Write::flush(&mut io::stdout())?;
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+                anyhow::bail!("Approval cancelled");
+            }
+
+            let mut store = approval::ApprovalStore::load()?;
+            store.approve(command.id, &command.command_string)?;
+            println!(
+                "✓ Approved '{}/{}' (ID: {})",
+                command.namespace, command.name, command.id
+            );
+        }
         Commands::Exec { command_id, args } => {
             // Execute command directly by ID
             let command = api_client
@@ -890,7 +1691,9 @@ async fn main() -> Result<()> {
                 .context("Failed to execute command")?;
 
             let templated_command = templating::resolve_arguments(&command.command_string, &args)?;
-            let resolved_command = secrets::resolve_secrets(&templated_command)?;
+            let providers =
+                secrets::providers_for_namespace(&settings.secret_providers, &command.namespace);
+            let resolved_command = secrets::resolve_secrets_with(&providers, &templated_command)?;
 
             let cwd = env::current_dir()?
                 .to_str()
@@ -902,7 +1705,9 @@ async fn main() -> Result<()> {
                 .to_string();
 
             execute_command_with_tracking(
-                &api_client,
+                api_client.as_ref(),
+                telemetry_guard,
+                &settings,
                 &command,
                 &user,
                 &hostname,
@@ -910,6 +1715,8 @@ async fn main() -> Result<()> {
                 &resolved_command,
                 "id",
                 &args,
+                true,
+                true,
             )
             .await?;
         }
@@ -938,16 +1745,23 @@ async fn main() -> Result<()> {
                 scope_hint: scope,
             };
 
-            let command = api_client
-                .recall_command_by_name(request)
-                .await
-                .context("Failed to find command")?;
+            let command = match api_client.recall_command_by_name(request).await {
+                Ok(command) => command,
+                Err(e) => {
+                    print_did_you_mean(api_client.as_ref(), &user, &name).await;
+                    return Err(e).context("Failed to find command");
+                }
+            };
 
             let templated_command = templating::resolve_arguments(&command.command_string, &args)?;
-            let resolved_command = secrets::resolve_secrets(&templated_command)?;
+            let providers =
+                secrets::providers_for_namespace(&settings.secret_providers, &command.namespace);
+            let resolved_command = secrets::resolve_secrets_with(&providers, &templated_command)?;
 
             execute_command_with_tracking(
-                &api_client,
+                api_client.as_ref(),
+                telemetry_guard,
+                &settings,
                 &command,
                 &user,
                 &hostname,
@@ -955,12 +1769,20 @@ async fn main() -> Result<()> {
                 &resolved_command,
                 "name",
                 &args,
+                true,
+                true,
             )
             .await?;
         }
         Commands::QuickSave { name, namespace } => {
+            if let Some(namespace) = &namespace {
+                require_valid_namespace(namespace)?;
+            }
+
             // Get the last command from shell history
-            let history_manager = history::HistoryManager::new();
+            let history_manager =
+                history::HistoryManager::with_ignore_patterns(&settings.history_ignore)
+                    .context("Failed to compile history_ignore patterns")?;
 
             if !history_manager.is_available() {
                 eprintln!(
@@ -980,7 +1802,7 @@ async fn main() -> Result<()> {
                     // Auto-detect namespace if not provided
                     let detected_namespace = namespace.unwrap_or_else(|| {
                         // Try to detect namespace from current directory
-                        detect_namespace_from_context().unwrap_or_else(|| "quick-save".to_string())
+                        detect_namespace(&settings).unwrap_or_else(|| "quick-save".to_string())
                     });
 
                     let cwd = env::current_dir()?
@@ -1000,6 +1822,7 @@ async fn main() -> Result<()> {
                         cwd: Some(cwd),
                         hostname: Some(hostname),
                         scope: "personal".to_string(),
+                        description: None,
                     };
 
                     let cmd = api_client
@@ -1027,6 +1850,28 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        Commands::Recent { count } => {
+            let history_manager =
+                history::HistoryManager::with_ignore_patterns(&settings.history_ignore)
+                    .context("Failed to compile history_ignore patterns")?;
+
+            if !history_manager.is_available() {
+                eprintln!(
+                    "Shell history integration not available: {}",
+                    history_manager.status_description()
+                );
+                return Ok(());
+            }
+
+            let ranked = history_manager.rank_recent(count, settings.rank_weights)?;
+            if ranked.is_empty() {
+                println!("No ranked commands found in shell history.");
+            } else {
+                for command in &ranked {
+                    println!("{command}");
+                }
+            }
+        }
         Commands::Do {
             command_parts,
             name,
@@ -1035,12 +1880,16 @@ async fn main() -> Result<()> {
             let command_string = shlex::try_join(command_parts.iter().map(|s| s.as_str()))
                 .context("Failed to join command parts")?;
 
+            if let Some(namespace) = &namespace {
+                require_valid_namespace(namespace)?;
+            }
+
             // Use provided name or auto-detect
             let final_name = name.unwrap_or_else(|| detect_name_from_command(&command_string));
 
             // Use provided namespace or auto-detect
             let final_namespace = namespace.unwrap_or_else(|| {
-                detect_namespace_from_context().unwrap_or_else(|| "ad-hoc".to_string())
+                detect_namespace(&settings).unwrap_or_else(|| "ad-hoc".to_string())
             });
 
             let cwd = env::current_dir()?
@@ -1059,6 +1908,7 @@ async fn main() -> Result<()> {
                 cwd: Some(cwd.clone()),
                 hostname: Some(hostname.clone()),
                 scope: "personal".to_string(),
+                description: None,
             };
             let saved_command = api_client
                 .save_command(new_command)
@@ -1072,7 +1922,9 @@ async fn main() -> Result<()> {
 
             // Execute using our tracking function
             execute_command_with_tracking(
-                &api_client,
+                api_client.as_ref(),
+                telemetry_guard,
+                &settings,
                 &saved_command,
                 &user,
                 &hostname,
@@ -1080,6 +1932,8 @@ async fn main() -> Result<()> {
                 &command_string,
                 "do",
                 &[],
+                true,
+                true,
             )
             .await?;
         }
@@ -1091,7 +1945,7 @@ async fn main() -> Result<()> {
             let name = detect_name_from_command(&command_string);
 
             // Use smart namespace detection instead of hardcoded "ad-hoc"
-            let namespace = detect_namespace_from_context().unwrap_or_else(|| "ad-hoc".to_string());
+            let namespace = detect_namespace(&settings).unwrap_or_else(|| "ad-hoc".to_string());
 
             let cwd = env::current_dir()?
                 .to_str()
@@ -1109,6 +1963,7 @@ async fn main() -> Result<()> {
                 cwd: Some(cwd.clone()),
                 hostname: Some(hostname.clone()),
                 scope: "personal".to_string(),
+                description: None,
             };
             let saved_command = api_client
                 .save_command(new_command)
@@ -1122,7 +1977,9 @@ async fn main() -> Result<()> {
 
             // Execute using our tracking function
             execute_command_with_tracking(
-                &api_client,
+                api_client.as_ref(),
+                telemetry_guard,
+                &settings,
                 &saved_command,
                 &user,
                 &hostname,
@@ -1130,9 +1987,19 @@ async fn main() -> Result<()> {
                 &command_string,
                 "shell",
                 &[],
+                true,
+                true,
             )
             .await?;
         }
+        Commands::Import {
+            file,
+            namespace,
+            batch_size,
+        } => {
+            require_valid_namespace(&namespace)?;
+            run_import(api_client.as_ref(), &user, file, &namespace, batch_size).await?;
+        }
         Commands::Recall(args) => {
             if args.is_empty() {
                 anyhow::bail!("Error: requires at least one argument: <id> or <namespace> <name>");
@@ -1143,14 +2010,22 @@ async fn main() -> Result<()> {
                 if let Ok(command_id) = args[0].parse::<i32>() {
                     // Direct execution by ID
                     let extra_args = &args[1..].to_vec();
-                    let command = api_client
-                        .execute_command(command_id, &user)
-                        .await
-                        .context("Failed to execute command by ID")?;
+                    let command = match api_client.execute_command(command_id, &user).await {
+                        Ok(command) => command,
+                        Err(e) => {
+                            print_did_you_mean(api_client.as_ref(), &user, &args[0]).await;
+                            return Err(e).context("Failed to execute command by ID");
+                        }
+                    };
 
                     let templated_command =
                         templating::resolve_arguments(&command.command_string, extra_args)?;
-                    let resolved_command = secrets::resolve_secrets(&templated_command)?;
+                    let providers = secrets::providers_for_namespace(
+                        &settings.secret_providers,
+                        &command.namespace,
+                    );
+                    let resolved_command =
+                        secrets::resolve_secrets_with(&providers, &templated_command)?;
 
                     let cwd = env::current_dir()?
                         .to_str()
@@ -1162,7 +2037,9 @@ async fn main() -> Result<()> {
                         .to_string();
 
                     execute_command_with_tracking(
-                        &api_client,
+                        api_client.as_ref(),
+                        telemetry_guard,
+                        &settings,
                         &command,
                         &user,
                         &hostname,
@@ -1170,6 +2047,8 @@ async fn main() -> Result<()> {
                         &resolved_command,
                         "id",
                         extra_args,
+                        true,
+                        true,
                     )
                     .await?;
 
@@ -1193,17 +2072,27 @@ async fn main() -> Result<()> {
                 .context("Invalid hostname")?
                 .to_string();
 
-            let command = api_client
+            let command = match api_client
                 .recall_command(namespace, name, &user, &hostname, &cwd)
                 .await
-                .context("Failed to recall command from server")?;
+            {
+                Ok(command) => command,
+                Err(e) => {
+                    print_did_you_mean(api_client.as_ref(), &user, name).await;
+                    return Err(e).context("Failed to recall command from server");
+                }
+            };
 
             let templated_command =
                 templating::resolve_arguments(&command.command_string, extra_args)?;
-            let resolved_command = secrets::resolve_secrets(&templated_command)?;
+            let providers =
+                secrets::providers_for_namespace(&settings.secret_providers, &command.namespace);
+            let resolved_command = secrets::resolve_secrets_with(&providers, &templated_command)?;
 
             execute_command_with_tracking(
-                &api_client,
+                api_client.as_ref(),
+                telemetry_guard,
+                &settings,
                 &command,
                 &user,
                 &hostname,
@@ -1211,11 +2100,76 @@ async fn main() -> Result<()> {
                 &resolved_command,
                 "namespace_name",
                 extra_args,
+                true,
+                true,
             )
             .await?;
         }
-        // This case is now handled before the API client is initialized.
+        Commands::Interactive => {
+            let cwd = env::current_dir()?
+                .to_str()
+                .context("Invalid CWD")?
+                .to_string();
+            let hostname = gethostname::gethostname()
+                .to_str()
+                .context("Invalid hostname")?
+                .to_string();
+
+            interactive::run(
+                api_client.as_ref(),
+                telemetry_guard,
+                &settings,
+                &user,
+                &hostname,
+                &cwd,
+            )
+            .await?;
+        }
+        Commands::Batch {
+            refs,
+            file,
+            continue_on_error,
+            parallelism,
+        } => {
+            let references = match file {
+                Some(path) => std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read batch file '{path}'"))?
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string)
+                    .collect(),
+                None => refs,
+            };
+            if references.is_empty() {
+                anyhow::bail!("No command references given; pass them as args or via --file");
+            }
+
+            let cwd = env::current_dir()?
+                .to_str()
+                .context("Invalid CWD")?
+                .to_string();
+            let hostname = gethostname::gethostname()
+                .to_str()
+                .context("Invalid hostname")?
+                .to_string();
+
+            batch::run(
+                api_client.as_ref(),
+                telemetry_guard,
+                &settings,
+                &user,
+                &hostname,
+                &cwd,
+                &references,
+                continue_on_error,
+                parallelism,
+            )
+            .await?;
+        }
+        // These cases are now handled before the API client is initialized.
         Commands::GenerateCompletions { .. } => unreachable!(),
+        Commands::Secret { .. } => unreachable!(),
     }
     Ok(())
 }
@@ -1269,6 +2223,7 @@ mod tests {
             last_used_at: None,
             use_count: 0,
             is_new: false,
+            description: None,
         }];
         let output = format_find_output(&commands);
         assert!(output.contains("ID:1"));
@@ -1280,50 +2235,218 @@ mod tests {
         assert!(output.contains("echo hi"));
     }
 
+    /// Asserts `path` is absolute (as every `tempfile::tempdir()` path is) and wraps it for
+    /// [`manifest::detect_namespace_from`], which takes an [`manifest::AbsPath`] rather than
+    /// reading the process' current directory.
+    fn abs(path: &std::path::Path) -> manifest::AbsPathBuf {
+        manifest::AbsPathBuf::new(path.to_path_buf()).unwrap()
+    }
+
     #[test]
     fn test_namespace_detection_with_package_json() {
         let temp_dir = tempfile::tempdir().unwrap();
         let package_json_path = temp_dir.path().join("package.json");
         std::fs::write(&package_json_path, "{}").unwrap();
 
-        // Change to the temp directory for testing
-        let original_dir = std::env::current_dir().unwrap();
-        std::env::set_current_dir(temp_dir.path()).unwrap();
-
-        let result = detect_namespace_from_context();
-
-        // Restore original directory
-        std::env::set_current_dir(original_dir).unwrap();
+        let (_root, namespace) = manifest::detect_namespace_from(
+            abs(temp_dir.path()).as_abs_path(),
+            &manifest::default_detector_order(),
+        )
+        .unwrap();
 
-        // Should return the temp directory name
-        assert!(result.is_some());
+        // No "name" field in package.json, so should fall back to the temp directory name.
         let dir_name = temp_dir.path().file_name().unwrap().to_str().unwrap();
-        assert_eq!(result, Some(dir_name.to_string()));
+        assert_eq!(namespace, dir_name);
     }
 
     #[test]
     fn test_namespace_detection_priority() {
-        // Test that namespace detection follows the correct priority order
+        // Cargo.toml's declared package name should win over both package.json's presence and
+        // the temp directory's own (randomly generated) name.
         let temp_dir = tempfile::tempdir().unwrap();
 
-        // Create both package.json and Cargo.toml to test priority
         let package_json_path = temp_dir.path().join("package.json");
         std::fs::write(&package_json_path, "{}").unwrap();
 
         let cargo_toml_path = temp_dir.path().join("Cargo.toml");
         std::fs::write(&cargo_toml_path, "[package]\nname = \"rust-project\"\n").unwrap();
 
-        // Change to the temp directory for testing
-        let original_dir = std::env::current_dir().unwrap();
-        std::env::set_current_dir(temp_dir.path()).unwrap();
+        let (_root, namespace) = manifest::detect_namespace_from(
+            abs(temp_dir.path()).as_abs_path(),
+            &manifest::default_detector_order(),
+        )
+        .unwrap();
 
-        let result = detect_namespace_from_context();
+        assert_eq!(namespace, "rust-project");
+    }
 
-        // Restore original directory
-        std::env::set_current_dir(original_dir).unwrap();
+    #[test]
+    fn test_namespace_detection_sanitizes_scoped_npm_name() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"name": "@scope/pkg"}"#,
+        )
+        .unwrap();
+
+        let (_root, namespace) = manifest::detect_namespace_from(
+            abs(temp_dir.path()).as_abs_path(),
+            &manifest::default_detector_order(),
+        )
+        .unwrap();
+
+        assert_eq!(namespace, "scope-pkg");
+    }
 
-        // Should pick directory name (package.json has higher priority)
-        let dir_name = temp_dir.path().file_name().unwrap().to_str().unwrap();
-        assert_eq!(result, Some(dir_name.to_string()));
+    #[test]
+    fn test_namespace_detection_walks_up_to_workspace_root() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/foo\"]\n",
+        )
+        .unwrap();
+
+        let sub_crate_src = temp_dir.path().join("crates").join("foo").join("src");
+        std::fs::create_dir_all(&sub_crate_src).unwrap();
+        std::fs::write(
+            temp_dir.path().join("crates").join("foo").join("Cargo.toml"),
+            "[package]\nname = \"foo\"\n",
+        )
+        .unwrap();
+
+        let (root, namespace) = manifest::detect_namespace_from(
+            abs(&sub_crate_src).as_abs_path(),
+            &manifest::default_detector_order(),
+        )
+        .unwrap();
+
+        assert_eq!(root.as_abs_path().as_path(), temp_dir.path());
+        let workspace_dir_name = temp_dir.path().file_name().unwrap().to_str().unwrap();
+        assert_eq!(namespace, workspace_dir_name);
+    }
+
+    #[test]
+    fn test_namespace_detection_stops_at_nearest_manifest_without_workspace() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sub_crate = temp_dir.path().join("crates").join("foo");
+        std::fs::create_dir_all(&sub_crate).unwrap();
+        std::fs::write(sub_crate.join("Cargo.toml"), "[package]\nname = \"foo\"\n").unwrap();
+
+        let (root, namespace) = manifest::detect_namespace_from(
+            abs(&sub_crate).as_abs_path(),
+            &manifest::default_detector_order(),
+        )
+        .unwrap();
+
+        assert_eq!(root.as_abs_path().as_path(), sub_crate);
+        let sub_crate_dir_name = sub_crate.file_name().unwrap().to_str().unwrap();
+        assert_eq!(namespace, sub_crate_dir_name);
+    }
+
+    #[test]
+    fn test_namespace_detection_go_module() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("go.mod"),
+            "module github.com/example/widget\n\ngo 1.22\n",
+        )
+        .unwrap();
+
+        let (_root, namespace) = manifest::detect_namespace_from(
+            abs(temp_dir.path()).as_abs_path(),
+            &manifest::default_detector_order(),
+        )
+        .unwrap();
+
+        assert_eq!(namespace, "widget");
+    }
+
+    #[test]
+    fn test_namespace_detection_respects_configured_order() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"name": "from-npm"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"from-pyproject\"\n",
+        )
+        .unwrap();
+
+        let order = vec![
+            manifest::ManifestEcosystem::Pyproject,
+            manifest::ManifestEcosystem::Npm,
+        ];
+        let (_root, namespace) =
+            manifest::detect_namespace_from(abs(temp_dir.path()).as_abs_path(), &order).unwrap();
+
+        assert_eq!(namespace, "from-pyproject");
+    }
+
+    #[test]
+    fn test_abs_path_buf_rejects_relative_paths() {
+        assert!(manifest::AbsPathBuf::new(std::path::PathBuf::from("relative/path")).is_err());
+    }
+
+    #[test]
+    fn test_namespace_detection_falls_back_on_invalid_declared_name() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"mod\"\n",
+        )
+        .unwrap();
+
+        let order = manifest::default_detector_order();
+        let (_root, namespace) =
+            manifest::detect_namespace_from(abs(temp_dir.path()).as_abs_path(), &order).unwrap();
+
+        // "mod" is a reserved Rust keyword, so the declared name is rejected and the directory
+        // name is used instead.
+        assert_eq!(namespace, temp_dir.path().file_name().unwrap().to_str().unwrap());
+    }
+
+    #[test]
+    fn test_validate_namespace_name() {
+        assert!(manifest::validate_namespace_name("rust").is_ok());
+        assert!(manifest::validate_namespace_name("my-project_2").is_ok());
+        assert_eq!(
+            manifest::validate_namespace_name(""),
+            Err(manifest::NamespaceNameError::Empty)
+        );
+        assert_eq!(
+            manifest::validate_namespace_name("   "),
+            Err(manifest::NamespaceNameError::Empty)
+        );
+        assert_eq!(
+            manifest::validate_namespace_name("foo/bar"),
+            Err(manifest::NamespaceNameError::ContainsSeparator)
+        );
+        assert_eq!(
+            manifest::validate_namespace_name("foo::bar"),
+            Err(manifest::NamespaceNameError::ContainsSeparator)
+        );
+        assert_eq!(
+            manifest::validate_namespace_name("impl"),
+            Err(manifest::NamespaceNameError::Reserved)
+        );
+        assert_eq!(
+            manifest::validate_namespace_name("CON"),
+            Err(manifest::NamespaceNameError::Reserved)
+        );
+    }
+
+    #[test]
+    fn test_require_valid_namespace() {
+        assert!(require_valid_namespace("rust").is_ok());
+        let err = require_valid_namespace("foo/bar").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'foo/bar' isn't a valid namespace: namespace name contains a path or namespace \
+             separator ('/', '\\', \"::\", or whitespace)"
+        );
     }
 }
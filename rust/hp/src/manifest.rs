@@ -0,0 +1,379 @@
+//! Manifest-based namespace detection for `hp`: walks up from a directory to the project root
+//! and derives a namespace from whichever ecosystem's manifest declares a package name, trying
+//! [`ManifestEcosystem`]s in a configurable order (see
+//! [`crate::config::Settings::namespace_detectors`]) so a polyglot monorepo can pick which
+//! ecosystem wins.
+//!
+//! Detection is a pure function of an [`AbsPath`]: nothing here reads or mutates the process'
+//! current directory, so tests can point it at a temp dir directly instead of racing other tests
+//! over `std::env::set_current_dir`.
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// An owned, asserted-absolute filesystem path.
+///
+/// Constructing one fails on a relative path, so anything holding an `AbsPathBuf` can treat it as
+/// independent of the process' current directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AbsPathBuf(PathBuf);
+
+impl AbsPathBuf {
+    /// Wraps `path`, failing if it isn't absolute.
+    pub fn new(path: PathBuf) -> Result<Self> {
+        if path.is_absolute() {
+            Ok(AbsPathBuf(path))
+        } else {
+            bail!("path '{}' is not absolute", path.display())
+        }
+    }
+
+    /// Reads the process' current directory and asserts it's absolute (which `current_dir()`
+    /// always returns).
+    pub fn current_dir() -> Result<Self> {
+        Self::new(std::env::current_dir()?)
+    }
+
+    pub fn as_abs_path(&self) -> AbsPath<'_> {
+        AbsPath(&self.0)
+    }
+}
+
+/// A borrowed, asserted-absolute filesystem path. See [`AbsPathBuf`] for the owned counterpart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AbsPath<'a>(&'a Path);
+
+impl<'a> AbsPath<'a> {
+    pub fn as_path(self) -> &'a Path {
+        self.0
+    }
+
+    pub fn join(self, segment: impl AsRef<Path>) -> PathBuf {
+        self.0.join(segment)
+    }
+
+    /// The parent directory, still asserted absolute (an absolute path's parent is always
+    /// absolute, so this never needs to fail).
+    pub fn parent(self) -> Option<AbsPath<'a>> {
+        self.0.parent().map(AbsPath)
+    }
+
+    pub fn file_name(self) -> Option<&'a str> {
+        self.0.file_name().and_then(|name| name.to_str())
+    }
+
+    pub fn to_path_buf(self) -> AbsPathBuf {
+        AbsPathBuf(self.0.to_path_buf())
+    }
+}
+
+/// One ecosystem `hp` knows how to detect a declared package name from. Order matters: the first
+/// ecosystem (by the configured order) whose manifest is present at the project root wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestEcosystem {
+    Cargo,
+    Npm,
+    Pyproject,
+    Go,
+    Composer,
+    Deno,
+    Pom,
+}
+
+impl ManifestEcosystem {
+    /// The manifest filename this ecosystem looks for directly under a candidate directory.
+    fn manifest_file(self) -> &'static str {
+        match self {
+            ManifestEcosystem::Cargo => "Cargo.toml",
+            ManifestEcosystem::Npm => "package.json",
+            ManifestEcosystem::Pyproject => "pyproject.toml",
+            ManifestEcosystem::Go => "go.mod",
+            ManifestEcosystem::Composer => "composer.json",
+            ManifestEcosystem::Deno => "deno.json",
+            ManifestEcosystem::Pom => "pom.xml",
+        }
+    }
+
+    /// Parses `contents` (the manifest file's full text) and returns the declared package name,
+    /// if any.
+    fn declared_name(self, contents: &str) -> Option<String> {
+        match self {
+            ManifestEcosystem::Cargo => contents
+                .parse::<toml::Value>()
+                .ok()?
+                .get("package")?
+                .get("name")?
+                .as_str()
+                .map(str::to_string),
+            ManifestEcosystem::Npm | ManifestEcosystem::Deno => {
+                serde_json::from_str::<serde_json::Value>(contents)
+                    .ok()?
+                    .get("name")?
+                    .as_str()
+                    .map(str::to_string)
+            }
+            ManifestEcosystem::Pyproject => {
+                let value = contents.parse::<toml::Value>().ok()?;
+                value
+                    .get("project")
+                    .and_then(|table| table.get("name"))
+                    .or_else(|| {
+                        value
+                            .get("tool")
+                            .and_then(|table| table.get("poetry"))
+                            .and_then(|table| table.get("name"))
+                    })
+                    .and_then(|name| name.as_str())
+                    .map(str::to_string)
+            }
+            ManifestEcosystem::Go => contents.lines().find_map(|line| {
+                line.trim()
+                    .strip_prefix("module ")
+                    .map(str::trim)
+                    .and_then(|module_path| module_path.rsplit('/').next())
+                    .map(str::to_string)
+            }),
+            // Composer's "name" is a "vendor/package" pair; the package segment is what's
+            // actually useful as a namespace.
+            ManifestEcosystem::Composer => serde_json::from_str::<serde_json::Value>(contents)
+                .ok()?
+                .get("name")?
+                .as_str()
+                .map(|name| name.rsplit('/').next().unwrap_or(name).to_string()),
+            // No XML crate is in use elsewhere in this codebase, so pull the first <artifactId>
+            // element out with a plain string scan rather than pulling one in for this alone.
+            ManifestEcosystem::Pom => {
+                let start = contents.find("<artifactId>")? + "<artifactId>".len();
+                let end = contents[start..].find("</artifactId>")? + start;
+                Some(contents[start..end].trim().to_string())
+            }
+        }
+    }
+}
+
+/// The detector order used when `namespace_detectors` isn't configured: Cargo and npm (the two
+/// ecosystems `detect_namespace_from_context` originally supported) first, then the rest in
+/// roughly descending order of how often they'd co-occur with those two in a monorepo.
+pub fn default_detector_order() -> Vec<ManifestEcosystem> {
+    vec![
+        ManifestEcosystem::Cargo,
+        ManifestEcosystem::Npm,
+        ManifestEcosystem::Pyproject,
+        ManifestEcosystem::Go,
+        ManifestEcosystem::Composer,
+        ManifestEcosystem::Deno,
+        ManifestEcosystem::Pom,
+    ]
+}
+
+/// Returns `true` if the `Cargo.toml` at `path` declares a `[workspace]` table.
+///
+/// This is a line scan for a `[workspace]` header rather than a TOML parse: we only need to know
+/// whether the table is present.
+fn is_workspace_manifest(path: &Path) -> bool {
+    std::fs::read_to_string(path)
+        .map(|contents| contents.lines().any(|line| line.trim() == "[workspace]"))
+        .unwrap_or(false)
+}
+
+/// Walks up from `start` looking for the project root: the nearest ancestor containing any
+/// manifest recognized by `order`.
+///
+/// A `Cargo.toml` declaring a `[workspace]` table wins over the nearest plain manifest regardless
+/// of `order`, so running `hp` from a sub-crate (e.g. `crates/foo/src`) resolves to the workspace
+/// root and every sub-crate shares one namespace. Climbing stops at a `.git` directory (the repo
+/// boundary) or the filesystem root, whichever comes first, so detection never escapes the
+/// project.
+fn find_project_root(start: AbsPath<'_>, order: &[ManifestEcosystem]) -> Option<AbsPathBuf> {
+    let mut nearest_manifest: Option<AbsPathBuf> = None;
+    let mut dir = Some(start);
+
+    while let Some(d) = dir {
+        let cargo_toml = d.join("Cargo.toml");
+        if cargo_toml.exists() && is_workspace_manifest(&cargo_toml) {
+            return Some(d.to_path_buf());
+        }
+
+        if order.iter().any(|ecosystem| d.join(ecosystem.manifest_file()).exists()) {
+            nearest_manifest.get_or_insert_with(|| d.to_path_buf());
+        }
+
+        if d.join(".git").exists() {
+            break;
+        }
+        dir = d.parent();
+    }
+
+    nearest_manifest
+}
+
+/// Sanitizes an arbitrary name (a manifest-declared package name or a directory name) into a
+/// namespace: keeps alphanumerics, `-`, and `_` as-is, and collapses any run of other characters
+/// (path separators, `@` scope markers, whitespace) into a single `-`. Leading/trailing separators
+/// are dropped, so a scoped npm name like `@scope/pkg` sanitizes to `scope-pkg`.
+pub fn sanitize_namespace_name(name: &str) -> Option<String> {
+    let mut out = String::with_capacity(name.len());
+    let mut last_was_sep = true; // swallow any leading separator-worthy characters
+    for ch in name.trim().chars() {
+        if ch.is_alphanumeric() || ch == '-' || ch == '_' {
+            out.push(ch);
+            last_was_sep = false;
+        } else if !last_was_sep {
+            out.push('-');
+            last_was_sep = true;
+        }
+    }
+    while out.ends_with('-') {
+        out.pop();
+    }
+    (!out.is_empty()).then_some(out)
+}
+
+/// Reads the project name declared by the first ecosystem in `order` whose manifest is present
+/// directly under `root`, alongside the ecosystem it came from (so a validation failure can be
+/// traced back to the right manifest file). Returns `None` when no configured ecosystem's
+/// manifest is present, parses, or declares a name, so callers fall back to the directory name.
+fn manifest_declared_name(
+    root: AbsPath<'_>,
+    order: &[ManifestEcosystem],
+) -> Option<(ManifestEcosystem, String)> {
+    order.iter().find_map(|&ecosystem| {
+        let contents = std::fs::read_to_string(root.join(ecosystem.manifest_file())).ok()?;
+        ecosystem.declared_name(&contents).map(|name| (ecosystem, name))
+    })
+}
+
+/// Why [`validate_namespace_name`] rejected a candidate namespace name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamespaceNameError {
+    /// Empty, or only whitespace.
+    Empty,
+    /// Contains a path separator (`/`, `\`), the `::` namespace separator, or whitespace.
+    ContainsSeparator,
+    /// Collides with a Rust keyword or a Windows-reserved device name (see
+    /// [`RESERVED_NAMESPACE_NAMES`]) — both would be awkward or broken wherever the namespace
+    /// ends up on disk, in a shell word, or in a URL path segment.
+    Reserved,
+}
+
+impl std::fmt::Display for NamespaceNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NamespaceNameError::Empty => write!(f, "namespace name is empty"),
+            NamespaceNameError::ContainsSeparator => write!(
+                f,
+                "namespace name contains a path or namespace separator ('/', '\\', \"::\", or whitespace)"
+            ),
+            NamespaceNameError::Reserved => write!(f, "namespace name is a reserved word"),
+        }
+    }
+}
+
+impl std::error::Error for NamespaceNameError {}
+
+/// Rust keywords and Windows-reserved device names: cargo rejects these as package names for the
+/// same reason `hp` rejects them as namespace names, since both end up in file paths and shell
+/// words.
+const RESERVED_NAMESPACE_NAMES: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try", "con", "prn", "aux",
+    "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9", "lpt1", "lpt2",
+    "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Validates `name` as a namespace: non-empty, free of path/namespace separators, and not a
+/// reserved word. Applied both to namespace names detected from context (see
+/// [`detect_namespace_from`]) and ones a user supplies directly (e.g. `hp save --namespace`).
+pub fn validate_namespace_name(name: &str) -> Result<(), NamespaceNameError> {
+    if name.trim().is_empty() {
+        return Err(NamespaceNameError::Empty);
+    }
+    if name.contains(['/', '\\']) || name.contains("::") || name.chars().any(char::is_whitespace) {
+        return Err(NamespaceNameError::ContainsSeparator);
+    }
+    if RESERVED_NAMESPACE_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(name))
+    {
+        return Err(NamespaceNameError::Reserved);
+    }
+    Ok(())
+}
+
+/// Finds the 1-indexed line and column of `declared_name`'s first appearance on a line that looks
+/// like a `name` field, for pointing a validation error at the offending manifest field the way
+/// cargo points `TOML parse error at line N, column M` at a bad value. Best-effort: a plain text
+/// scan rather than a full parse with spans, since no manifest in this codebase is parsed with a
+/// span-tracking deserializer.
+fn locate_declared_name(manifest_contents: &str, declared_name: &str) -> Option<(usize, usize)> {
+    manifest_contents.lines().enumerate().find_map(|(line_no, line)| {
+        let contains_name_field = line.contains("name") || line.contains("artifactId");
+        let column = line.find(declared_name)?;
+        contains_name_field.then_some((line_no + 1, column + 1))
+    })
+}
+
+/// Detect namespace from directory context, starting at `start`, trying ecosystems in `order`.
+///
+/// Walks up to the project/workspace root via [`find_project_root`], then prefers the first
+/// matching ecosystem's declared package name (via [`manifest_declared_name`], sanitized with
+/// [`sanitize_namespace_name`]) over the root directory's own name, falling back to `start` itself
+/// when no manifest is found anywhere above it. Returns both the resolved root and the namespace
+/// so callers can cache the root for later use.
+///
+/// A declared name that fails [`validate_namespace_name`] is reported to stderr with its location
+/// in the manifest (via [`locate_declared_name`]) and discarded in favor of the directory name; a
+/// directory name that itself fails validation is still used (there's nothing better to fall back
+/// to) but is reported the same way, suggesting `--namespace` as an override.
+///
+/// Pure over `start`: no ambient state (the process' current directory) is read, so callers that
+/// want current-directory behavior must pass [`AbsPathBuf::current_dir`] explicitly (see
+/// [`crate::detect_namespace_from_context`] for `hp`'s CLI wrapper).
+pub fn detect_namespace_from(
+    start: AbsPath<'_>,
+    order: &[ManifestEcosystem],
+) -> Option<(AbsPathBuf, String)> {
+    let root = find_project_root(start, order).unwrap_or_else(|| start.to_path_buf());
+
+    if let Some((ecosystem, raw_name)) = manifest_declared_name(root.as_abs_path(), order) {
+        let candidate = sanitize_namespace_name(&raw_name)
+            .ok_or(NamespaceNameError::Empty)
+            .and_then(|sanitized| validate_namespace_name(&sanitized).map(|()| sanitized));
+
+        match candidate {
+            Ok(namespace) => return Some((root, namespace)),
+            Err(reason) => {
+                let manifest_path = root.join(ecosystem.manifest_file());
+                let location = std::fs::read_to_string(&manifest_path)
+                    .ok()
+                    .and_then(|contents| locate_declared_name(&contents, &raw_name));
+                match location {
+                    Some((line, column)) => eprintln!(
+                        "Warning: ignoring namespace name '{raw_name}' declared in {}:{line}:{column} \
+                         ({reason}); falling back to the directory name",
+                        manifest_path.display()
+                    ),
+                    None => eprintln!(
+                        "Warning: ignoring namespace name '{raw_name}' declared in {} ({reason}); \
+                         falling back to the directory name",
+                        manifest_path.display()
+                    ),
+                }
+            }
+        }
+    }
+
+    let dir_name = root.as_abs_path().file_name()?.to_string();
+    if let Err(reason) = validate_namespace_name(&dir_name) {
+        eprintln!(
+            "Warning: directory name '{dir_name}' isn't a valid namespace ({reason}); pass \
+             --namespace to override this auto-detected value"
+        );
+    }
+
+    Some((root, dir_name))
+}
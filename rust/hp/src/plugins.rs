@@ -0,0 +1,348 @@
+//! Subprocess plugin subsystem: discovers `hp-plugin-*` executables in the plugins directory and
+//! talks to each over a line-delimited JSON-RPC protocol on its stdin/stdout (the same idea as
+//! nushell's plugin protocol), so namespace detection, secret resolution, command
+//! transformation, and suggestion ranking can be extended without recompiling `hp`.
+//!
+//! Each plugin is spawned once at startup and probed with a `{"id":0,"method":"capabilities"}`
+//! request; its reply declares which [`Hook`]s it implements. From then on, each hook call is one
+//! JSON request line in, one JSON response line out, bounded by [`PLUGIN_TIMEOUT`] so a hung or
+//! misbehaving plugin can never block `hp` itself — callers fall back to the built-in heuristic
+//! on any error, timeout, or absent plugin. A plugin that times out once is marked dead (see
+//! [`Plugin::call_with_timeout`]) so a hang doesn't also leak one OS thread per subsequent call
+//! for the rest of the process's life.
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// A hook point a plugin can declare support for in its `capabilities` response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hook {
+    ResolveSecret,
+    DetectNamespace,
+    TransformCommand,
+    Suggest,
+}
+
+/// How long to wait for a plugin to answer a single request before giving up on it and falling
+/// back to the built-in heuristic for that call.
+const PLUGIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// One JSON-RPC request line written to a plugin's stdin.
+#[derive(Serialize)]
+struct RpcRequest<'a> {
+    id: u64,
+    method: &'a str,
+    params: Value,
+}
+
+/// One JSON-RPC response line read from a plugin's stdout. Exactly one of `result`/`error` is
+/// meaningful, matching the `id, result, error` shape described for the protocol.
+#[derive(Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Value,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CapabilitiesResult {
+    #[serde(default)]
+    hooks: Vec<Hook>,
+}
+
+/// A running plugin subprocess, kept alive for the life of the process so each hook call costs
+/// one request/response line instead of a fresh spawn.
+struct Plugin {
+    name: String,
+    hooks: HashSet<Hook>,
+    stdin: Mutex<ChildStdin>,
+    stdout: Mutex<BufReader<ChildStdout>>,
+    next_id: AtomicU64,
+    /// Set once a call to this plugin has timed out. A timed-out call's helper thread is left
+    /// permanently blocked reading `stdout` (holding its lock) if the plugin never answers or
+    /// closes its pipes, so once that's happened we stop spawning new helper threads that would
+    /// just pile up blocked on the same held lock forever — the plugin is treated as dead for the
+    /// rest of the process's life instead.
+    dead: AtomicBool,
+    _child: Mutex<Child>,
+}
+
+impl Plugin {
+    /// Spawns `path` and probes it with a `capabilities` request. Returns `None` (killing the
+    /// child) if the executable can't be spawned, doesn't answer within [`PLUGIN_TIMEOUT`], or
+    /// declares no hooks at all.
+    fn spawn(path: &Path) -> Option<Arc<Self>> {
+        let name = path.file_name()?.to_str()?.to_string();
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let stdin = child.stdin.take()?;
+        let stdout = BufReader::new(child.stdout.take()?);
+
+        let (stdin, stdout, hooks) = match probe_capabilities(stdin, stdout, &name) {
+            Ok(probed) => probed,
+            Err(e) => {
+                eprintln!("Warning: plugin '{name}' failed its capabilities probe: {e:#}");
+                let _ = child.kill();
+                return None;
+            }
+        };
+        if hooks.is_empty() {
+            let _ = child.kill();
+            return None;
+        }
+
+        Some(Arc::new(Plugin {
+            name,
+            hooks,
+            stdin: Mutex::new(stdin),
+            stdout: Mutex::new(stdout),
+            next_id: AtomicU64::new(1),
+            dead: AtomicBool::new(false),
+            _child: Mutex::new(child),
+        }))
+    }
+
+    /// Writes one JSON-RPC request line and reads one JSON-RPC response line. Blocking, with no
+    /// timeout of its own — callers needing a bound should go through [`Plugin::call_with_timeout`].
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let request = serde_json::to_string(&RpcRequest { id, method, params })
+            .context("Failed to serialize plugin request")?;
+
+        {
+            let mut stdin = self.stdin.lock().unwrap();
+            writeln!(stdin, "{request}").context("Failed to write to plugin stdin")?;
+            stdin.flush().ok();
+        }
+
+        let mut line = String::new();
+        {
+            let mut stdout = self.stdout.lock().unwrap();
+            let bytes_read = stdout
+                .read_line(&mut line)
+                .context("Failed to read from plugin stdout")?;
+            if bytes_read == 0 {
+                bail!("Plugin '{}' closed its stdout", self.name);
+            }
+        }
+
+        let response: RpcResponse = serde_json::from_str(line.trim())
+            .with_context(|| format!("Plugin '{}' sent a malformed response", self.name))?;
+        if let Some(error) = response.error {
+            bail!("Plugin '{}' returned an error: {}", self.name, error);
+        }
+        Ok(response.result)
+    }
+
+    /// Runs [`Plugin::call`] on a helper thread and waits at most [`PLUGIN_TIMEOUT`], so a plugin
+    /// that never replies can't hang `hp`. A plugin that has already timed out once is treated as
+    /// dead and short-circuited to `Ok(None)` without spawning another helper thread: its stdout
+    /// is presumed permanently held by the abandoned thread from the first timeout, and every
+    /// later call would just block forever trying to acquire that same lock, leaking one thread
+    /// per call for the rest of the process's life.
+    fn call_with_timeout<T: serde::de::DeserializeOwned + Send + 'static>(
+        self: &Arc<Self>,
+        method: &str,
+        params: Value,
+    ) -> Result<Option<T>> {
+        if self.dead.load(Ordering::SeqCst) {
+            return Ok(None);
+        }
+
+        let plugin = Arc::clone(self);
+        let method = method.to_string();
+        let method_for_call = method.clone();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(plugin.call(&method_for_call, params));
+        });
+
+        match rx.recv_timeout(PLUGIN_TIMEOUT) {
+            Ok(Ok(value)) => Ok(serde_json::from_value(value).ok()),
+            Ok(Err(e)) => Err(e),
+            Err(_) => {
+                self.dead.store(true, Ordering::SeqCst);
+                eprintln!(
+                    "Warning: plugin '{}' timed out after {:?} on '{}'; treating it as dead for the rest of this run",
+                    self.name, PLUGIN_TIMEOUT, method
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Writes the `capabilities` request and reads the response on a helper thread, bounded by
+/// [`PLUGIN_TIMEOUT`], since at this point there's no `Plugin`/`Arc` yet to hang the call off of.
+/// Returns the streams back (to be wrapped into a [`Plugin`]) alongside the declared hooks.
+fn probe_capabilities(
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    name: &str,
+) -> Result<(ChildStdin, BufReader<ChildStdout>, HashSet<Hook>)> {
+    let name = name.to_string();
+    let name_for_thread = name.clone();
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        let name = name_for_thread;
+        let result = (|| -> Result<(ChildStdin, BufReader<ChildStdout>, HashSet<Hook>)> {
+            let mut stdin = stdin;
+            let mut stdout = stdout;
+            let request = serde_json::to_string(&RpcRequest {
+                id: 0,
+                method: "capabilities",
+                params: Value::Null,
+            })?;
+            writeln!(stdin, "{request}").context("Failed to write to plugin stdin")?;
+            stdin.flush().ok();
+
+            let mut line = String::new();
+            let bytes_read = stdout
+                .read_line(&mut line)
+                .context("Failed to read from plugin stdout")?;
+            if bytes_read == 0 {
+                bail!("Plugin '{}' closed its stdout", name);
+            }
+
+            let response: RpcResponse = serde_json::from_str(line.trim())
+                .with_context(|| format!("Plugin '{}' sent a malformed response", name))?;
+            if let Some(error) = response.error {
+                bail!("Plugin '{}' returned an error: {}", name, error);
+            }
+            let capabilities: CapabilitiesResult = serde_json::from_value(response.result)
+                .with_context(|| format!("Plugin '{}' sent invalid capabilities", name))?;
+
+            Ok((stdin, stdout, capabilities.hooks.into_iter().collect()))
+        })();
+        let _ = tx.send(result);
+    });
+
+    rx.recv_timeout(PLUGIN_TIMEOUT)
+        .with_context(|| format!("Plugin '{name}' timed out answering capabilities"))?
+}
+
+/// The plugins discovered at startup, each already probed for which [`Hook`]s it implements.
+pub struct PluginManager {
+    plugins: Vec<Arc<Plugin>>,
+}
+
+impl PluginManager {
+    /// Scans `dir` for executables named `hp-plugin-*`, spawning and probing each. Plugins that
+    /// fail to spawn or to answer the capabilities probe are silently skipped.
+    fn discover(dir: &Path) -> Vec<Arc<Plugin>> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("hp-plugin-"))
+            })
+            .filter_map(|path| Plugin::spawn(&path))
+            .collect()
+    }
+
+    fn for_hook(&self, hook: Hook) -> impl Iterator<Item = &Arc<Plugin>> {
+        self.plugins
+            .iter()
+            .filter(move |plugin| plugin.hooks.contains(&hook))
+    }
+
+    /// Asks each plugin implementing [`Hook::DetectNamespace`], in discovery order, for a
+    /// namespace to use for `cwd`; returns the first non-empty answer.
+    pub fn detect_namespace(&self, cwd: &str) -> Option<String> {
+        for plugin in self.for_hook(Hook::DetectNamespace) {
+            if let Ok(Some(namespace)) = plugin
+                .call_with_timeout::<String>("detect_namespace", serde_json::json!({ "cwd": cwd }))
+            {
+                if !namespace.is_empty() {
+                    return Some(namespace);
+                }
+            }
+        }
+        None
+    }
+
+    /// Asks each plugin implementing [`Hook::ResolveSecret`], in discovery order, to resolve
+    /// `name`; returns the first `Some` answer, so this can sit in the same provider chain as
+    /// the environment, vault, keyring, and prompt providers.
+    pub fn resolve_secret(&self, name: &str) -> Option<String> {
+        for plugin in self.for_hook(Hook::ResolveSecret) {
+            if let Ok(Some(value)) = plugin
+                .call_with_timeout::<String>("resolve_secret", serde_json::json!({ "name": name }))
+            {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// Asks each plugin implementing [`Hook::TransformCommand`], in discovery order, to rewrite
+    /// `command_string` (e.g. to inject a wrapper or normalize flags) before execution; returns
+    /// the first rewrite offered, or `None` if no plugin implements the hook or all decline.
+    pub fn transform_command(&self, command_string: &str) -> Option<String> {
+        for plugin in self.for_hook(Hook::TransformCommand) {
+            if let Ok(Some(transformed)) = plugin.call_with_timeout::<String>(
+                "transform_command",
+                serde_json::json!({ "command": command_string }),
+            ) {
+                return Some(transformed);
+            }
+        }
+        None
+    }
+
+    /// Collects extra suggestions from every plugin implementing [`Hook::Suggest`] for the given
+    /// context, to be merged with (not replace) the server's own suggestions.
+    pub fn suggest(&self, cwd: &str, partial: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        for plugin in self.for_hook(Hook::Suggest) {
+            if let Ok(Some(candidates)) = plugin.call_with_timeout::<Vec<String>>(
+                "suggest",
+                serde_json::json!({ "cwd": cwd, "partial": partial }),
+            ) {
+                suggestions.extend(candidates);
+            }
+        }
+        suggestions
+    }
+}
+
+/// The plugins directory: `$HIPROC_PLUGINS_DIR` if set, otherwise `<config_dir>/plugins` next to
+/// `config.toml` and the secret vault.
+fn plugins_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("HIPROC_PLUGINS_DIR") {
+        if !dir.is_empty() {
+            return Some(PathBuf::from(dir));
+        }
+    }
+    crate::config::config_dir().map(|dir| dir.join("plugins"))
+}
+
+/// The process-wide plugin manager, discovered and probed at most once per run.
+pub fn plugin_manager() -> &'static PluginManager {
+    static PLUGIN_MANAGER: OnceLock<PluginManager> = OnceLock::new();
+    PLUGIN_MANAGER.get_or_init(|| PluginManager {
+        plugins: plugins_dir()
+            .map(|dir| PluginManager::discover(&dir))
+            .unwrap_or_default(),
+    })
+}
@@ -1,39 +1,373 @@
 //! Handles the detection and substitution of secrets in command strings.
-use anyhow::{Context, Result};
+use crate::vault::SecretVault;
+use anyhow::{anyhow, bail, Context, Result};
 use regex::Regex;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::env;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+use zeroize::Zeroize;
 
-/// Finds all placeholders (e.g., `{{SECRET_NAME}}`) in a command string,
-/// resolves them, and returns the substituted command.
-pub fn resolve_secrets(command_string: &str) -> Result<String> {
-    let re = Regex::new(r"\{\{([a-zA-Z_][a-zA-Z0-9_]*)\}\}")?;
-    let mut resolved_secrets: HashMap<String, String> = HashMap::new();
-    let mut final_command = command_string.to_string();
+/// A source `resolve_secrets_with` consults, in order, to resolve a `{{secret:NAME}}`
+/// placeholder.
+pub trait SecretProvider {
+    /// Attempts to resolve `name`, returning `None` if this provider has no answer for it so
+    /// the caller should fall through to the next provider in the chain.
+    fn resolve(&self, name: &str) -> Result<Option<String>>;
+}
+
+/// Process environment variables, keyed by secret name.
+struct EnvProvider;
+
+impl SecretProvider for EnvProvider {
+    fn resolve(&self, name: &str) -> Result<Option<String>> {
+        Ok(env::var(name).ok())
+    }
+}
+
+/// The encrypted local vault (see [`crate::vault`]), unsealed at most once per run.
+struct VaultProvider;
+
+impl SecretProvider for VaultProvider {
+    fn resolve(&self, name: &str) -> Result<Option<String>> {
+        // `SecretVault::get` returns the plaintext as `Zeroizing<String>` so it's scrubbed from
+        // memory as soon as this scope ends; we have to hand back a plain `String` here since
+        // that's what `SecretProvider` promises every provider, but the extra copy is as
+        // short-lived as `resolve_secrets_with` itself.
+        match vault().lock().unwrap().as_ref() {
+            Some(v) => Ok(v.get(name)?.map(|value| value.to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The OS keyring/keychain (Keychain on macOS, Secret Service on Linux, Credential Manager on
+/// Windows), under service `"hiproc"` with the secret name as the account.
+struct KeyringProvider;
+
+impl SecretProvider for KeyringProvider {
+    fn resolve(&self, name: &str) -> Result<Option<String>> {
+        let entry =
+            keyring::Entry::new("hiproc", name).context("Failed to open OS keyring entry")?;
+        match entry.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e).context("Failed to read secret from OS keyring"),
+        }
+    }
+}
+
+/// Runs an external resolver program (e.g. `op read`, `vault kv get`, `pass show`) with `{name}`
+/// substituted into its arguments, and captures trimmed stdout as the secret value.
+struct ExternalCommandProvider {
+    command: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl SecretProvider for ExternalCommandProvider {
+    fn resolve(&self, name: &str) -> Result<Option<String>> {
+        let args: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| arg.replace("{name}", name))
+            .collect();
+
+        let mut child = Command::new(&self.command)
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to spawn secret resolver '{}'", self.command))?;
+
+        // Drain stdout on a separate thread so a resolver that writes more than the pipe buffer
+        // holds can't deadlock against us waiting on it below.
+        let mut stdout = child
+            .stdout
+            .take()
+            .context("Secret resolver had no stdout")?;
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stdout.read_to_end(&mut buf);
+            let _ = tx.send(buf);
+        });
+
+        let output = match rx.recv_timeout(self.timeout) {
+            Ok(output) => output,
+            Err(_) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                bail!(
+                    "Secret resolver '{}' timed out after {:?}",
+                    self.command,
+                    self.timeout
+                );
+            }
+        };
+
+        let status = child.wait().context("Failed to wait for secret resolver")?;
+        if !status.success() {
+            bail!("Secret resolver '{}' exited with {}", self.command, status);
+        }
+
+        let value =
+            String::from_utf8(output).context("Secret resolver output was not valid UTF-8")?;
+        Ok(Some(value.trim_end_matches(['\n', '\r']).to_string()))
+    }
+}
+
+/// Every loaded subprocess plugin implementing the `resolve_secret` hook.
+struct PluginProvider;
+
+impl SecretProvider for PluginProvider {
+    fn resolve(&self, name: &str) -> Result<Option<String>> {
+        Ok(crate::plugins::plugin_manager().resolve_secret(name))
+    }
+}
+
+/// Interactive passphrase-style prompt, offering to save the entered value into the vault.
+struct PromptProvider;
+
+impl SecretProvider for PromptProvider {
+    fn resolve(&self, name: &str) -> Result<Option<String>> {
+        let value = rpassword::prompt_password(format!("Enter value for secret '{}': ", name))
+            .context("Failed to read secret from prompt")?;
+        offer_to_save(name, &value)?;
+        Ok(Some(value))
+    }
+}
 
-    for cap in re.captures_iter(command_string) {
-        let placeholder = cap.get(0).unwrap().as_str();
-        let secret_name = cap.get(1).unwrap().as_str();
+/// Declarative description of a [`SecretProvider`], as configured in `hiproc.toml`'s
+/// `[secret_providers]` table, converted to a live provider via [`SecretProviderSpec::build`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SecretProviderSpec {
+    /// Process environment variables.
+    Env,
+    /// The encrypted local vault.
+    Vault,
+    /// The OS keyring/keychain.
+    Keyring,
+    /// An external resolver program; `{name}` in `args` is replaced with the secret's name.
+    External {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default = "default_external_timeout_secs")]
+        timeout_secs: u64,
+    },
+    /// Any loaded subprocess plugin implementing the `resolve_secret` hook (see [`crate::plugins`]).
+    Plugin,
+    /// Interactive prompt, offering to save into the vault.
+    Prompt,
+}
+
+fn default_external_timeout_secs() -> u64 {
+    5
+}
 
-        if let Some(secret_value) = resolved_secrets.get(secret_name) {
-            final_command = final_command.replace(placeholder, secret_value);
-            continue;
+impl SecretProviderSpec {
+    fn build(&self) -> Box<dyn SecretProvider> {
+        match self {
+            SecretProviderSpec::Env => Box::new(EnvProvider),
+            SecretProviderSpec::Vault => Box::new(VaultProvider),
+            SecretProviderSpec::Keyring => Box::new(KeyringProvider),
+            SecretProviderSpec::External {
+                command,
+                args,
+                timeout_secs,
+            } => Box::new(ExternalCommandProvider {
+                command: command.clone(),
+                args: args.clone(),
+                timeout: Duration::from_secs(*timeout_secs),
+            }),
+            SecretProviderSpec::Plugin => Box::new(PluginProvider),
+            SecretProviderSpec::Prompt => Box::new(PromptProvider),
         }
+    }
+}
+
+/// Per-namespace ordering of secret providers, so e.g. CI hosts can resolve secrets from the
+/// environment only while workstations prefer the OS keyring or vault ahead of a prompt.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SecretProviderSettings {
+    /// Provider chain used for a namespace with no entry in `namespaces`.
+    #[serde(default = "default_provider_chain")]
+    pub default: Vec<SecretProviderSpec>,
+    /// Provider chain overrides, keyed by command namespace.
+    #[serde(default)]
+    pub namespaces: HashMap<String, Vec<SecretProviderSpec>>,
+}
+
+impl Default for SecretProviderSettings {
+    fn default() -> Self {
+        SecretProviderSettings {
+            default: default_provider_chain(),
+            namespaces: HashMap::new(),
+        }
+    }
+}
+
+/// The provider chain used when no configuration is present: environment, then the encrypted
+/// vault, then any loaded plugin, then an interactive prompt. This is `resolve_secrets`'s
+/// behavior.
+fn default_provider_chain() -> Vec<SecretProviderSpec> {
+    vec![
+        SecretProviderSpec::Env,
+        SecretProviderSpec::Vault,
+        SecretProviderSpec::Plugin,
+        SecretProviderSpec::Prompt,
+    ]
+}
+
+/// Builds the live provider chain configured for `namespace`, falling back to `settings.default`
+/// if `namespace` has no override.
+pub fn providers_for_namespace(
+    settings: &SecretProviderSettings,
+    namespace: &str,
+) -> Vec<Box<dyn SecretProvider>> {
+    settings
+        .namespaces
+        .get(namespace)
+        .unwrap_or(&settings.default)
+        .iter()
+        .map(SecretProviderSpec::build)
+        .collect()
+}
 
-        // 1. Try to get the secret from an environment variable
-        if let Ok(secret_value) = env::var(secret_name) {
-            resolved_secrets.insert(secret_name.to_string(), secret_value.clone());
-            final_command = final_command.replace(placeholder, &secret_value);
-            continue;
+/// The vault unsealed for this run, if one exists and was successfully unlocked. Populated at
+/// most once: see [`vault`].
+static VAULT: Mutex<Option<SecretVault>> = Mutex::new(None);
+/// Whether we've already prompted for the vault master passphrase this run, so a miss on
+/// `VAULT` doesn't trigger a repeat prompt for every remaining placeholder.
+static VAULT_UNLOCK_ATTEMPTED: AtomicBool = AtomicBool::new(false);
+
+/// Returns the process-wide vault lock, unsealing it on the first call (prompting for the
+/// master passphrase) if a vault file exists. Later calls reuse the same unsealed vault, or the
+/// same "no vault" outcome, without prompting again.
+fn vault() -> &'static Mutex<Option<SecretVault>> {
+    if !VAULT_UNLOCK_ATTEMPTED.swap(true, Ordering::SeqCst) {
+        if matches!(SecretVault::exists(), Ok(true)) {
+            match rpassword::prompt_password("Enter vault master passphrase: ")
+                .context("Failed to read vault passphrase")
+                .and_then(|mut passphrase| {
+                    let result = SecretVault::unlock(&passphrase);
+                    passphrase.zeroize();
+                    result
+                }) {
+                Ok(unlocked) => *VAULT.lock().unwrap() = Some(unlocked),
+                Err(e) => eprintln!("Warning: could not unlock the secret vault: {e:#}"),
+            }
         }
+    }
+    &VAULT
+}
+
+/// Finds all `{{secret:NAME}}` placeholders (the form [`crate::templating`] leaves in place) in a
+/// command string, resolves them against the default provider chain (environment, then the
+/// encrypted vault, then any loaded plugin, then an interactive prompt), and returns the
+/// substituted command.
+pub fn resolve_secrets(command_string: &str) -> Result<String> {
+    let providers: Vec<Box<dyn SecretProvider>> = default_provider_chain()
+        .iter()
+        .map(SecretProviderSpec::build)
+        .collect();
+    resolve_secrets_with(&providers, command_string)
+}
+
+/// Finds all `{{secret:NAME}}` placeholders in a command string, resolves each against
+/// `providers` in order (the first provider to return `Some` wins), and returns the substituted
+/// command. An escaped `\{{secret:NAME}}` is left as the literal `{{secret:NAME}}` instead of
+/// being resolved.
+pub fn resolve_secrets_with(
+    providers: &[Box<dyn SecretProvider>],
+    command_string: &str,
+) -> Result<String> {
+    let re = Regex::new(
+        r"\\(\{\{secret:[a-zA-Z_][a-zA-Z0-9_]*\}\})|\{\{secret:([a-zA-Z_][a-zA-Z0-9_]*)\}\}",
+    )?;
+    let mut resolved_secrets: HashMap<String, String> = HashMap::new();
+    let mut error = None;
+
+    let final_command = re
+        .replace_all(command_string, |caps: &regex::Captures| {
+            if let Some(escaped) = caps.get(1) {
+                // Escaped `\{{secret:NAME}}` — emit the literal placeholder, unresolved.
+                return escaped.as_str().to_string();
+            }
+            if error.is_some() {
+                return caps[0].to_string();
+            }
 
-        // 2. If not in env, prompt the user securely
-        let secret_value =
-            rpassword::prompt_password(format!("Enter value for secret '{}': ", secret_name))
-                .context("Failed to read secret from prompt")?;
-        resolved_secrets.insert(secret_name.to_string(), secret_value.clone());
-        final_command = final_command.replace(placeholder, &secret_value);
+            let secret_name = &caps[2];
+            if let Some(secret_value) = resolved_secrets.get(secret_name) {
+                return secret_value.clone();
+            }
+
+            let mut resolved = None;
+            for provider in providers {
+                match provider.resolve(secret_name) {
+                    Ok(Some(value)) => {
+                        resolved = Some(value);
+                        break;
+                    }
+                    Ok(None) => continue,
+                    Err(e) => {
+                        error = Some(e);
+                        return caps[0].to_string();
+                    }
+                }
+            }
+
+            match resolved {
+                Some(secret_value) => {
+                    resolved_secrets.insert(secret_name.to_string(), secret_value.clone());
+                    secret_value
+                }
+                None => {
+                    error = Some(anyhow!(
+                        "No configured provider could resolve secret '{}'",
+                        secret_name
+                    ));
+                    caps[0].to_string()
+                }
+            }
+        })
+        .into_owned();
+
+    if let Some(e) = error {
+        return Err(e);
     }
 
     Ok(final_command)
 }
+
+/// Asks whether to persist a newly-entered secret into the vault, creating the vault (prompting
+/// for a new master passphrase) on first use.
+fn offer_to_save(name: &str, value: &str) -> Result<()> {
+    print!("Save '{}' to the encrypted secret vault? [y/N] ", name);
+    io::stdout().flush().ok();
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context("Failed to read confirmation")?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        return Ok(());
+    }
+
+    let mut guard = vault().lock().unwrap();
+    if guard.is_none() {
+        let mut passphrase =
+            rpassword::prompt_password("Create a master passphrase for the secret vault: ")
+                .context("Failed to read new vault passphrase")?;
+        let created = SecretVault::create(&passphrase);
+        passphrase.zeroize();
+        *guard = Some(created?);
+    }
+    guard.as_mut().unwrap().set(name, value)
+}
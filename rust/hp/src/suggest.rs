@@ -0,0 +1,127 @@
+//! "Did you mean" fuzzy suggestions for `hp run`/`hp recall` misses, computed locally against
+//! [`crate::backend::Backend::get_all_user_commands`] (the same call `Edit` already makes) rather
+//! than the server, since a typo shouldn't need a second round trip to fix.
+use crate::api::Command;
+
+/// Standard Levenshtein edit-distance DP table: `d[i][j]` is the cost to transform the first `i`
+/// characters of `a` into the first `j` characters of `b`, with cost 0 on a matching character
+/// and `1 + min(insert, delete, substitute)` otherwise.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (n, m) = (a.len(), b.len());
+
+    let mut d = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in d.iter_mut().enumerate().take(n + 1) {
+        row[0] = i;
+    }
+    for j in 0..=m {
+        d[0][j] = j;
+    }
+
+    for i in 1..=n {
+        for j in 1..=m {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+        }
+    }
+
+    d[n][m]
+}
+
+/// One "did you mean" candidate: a saved command plus its edit distance from the typed name.
+pub struct Suggestion<'a> {
+    pub command: &'a Command,
+    pub distance: usize,
+}
+
+/// How close a candidate must be to be offered: `max(2, typed.len() / 3)`, so short typos stay
+/// strict while longer names tolerate proportionally more drift.
+fn threshold(typed: &str) -> usize {
+    (typed.chars().count() / 3).max(2)
+}
+
+/// Finds the closest matches to `typed` among `commands`, scoring each by the smaller of its
+/// Levenshtein distance to the bare `name` and to the full `namespace/name` string, keeping only
+/// candidates within [`threshold`] and returning up to `limit`, closest first.
+pub fn did_you_mean<'a>(typed: &str, commands: &'a [Command], limit: usize) -> Vec<Suggestion<'a>> {
+    let max_distance = threshold(typed);
+
+    let mut suggestions: Vec<Suggestion> = commands
+        .iter()
+        .filter_map(|command| {
+            let namespaced = format!("{}/{}", command.namespace, command.name);
+            let distance = levenshtein(typed, &command.name).min(levenshtein(typed, &namespaced));
+            (distance <= max_distance).then_some(Suggestion { command, distance })
+        })
+        .collect();
+
+    suggestions.sort_by_key(|s| s.distance);
+    suggestions.truncate(limit);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn command(id: i32, namespace: &str, name: &str) -> Command {
+        Command {
+            id,
+            command_string: format!("echo {name}"),
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            user: None,
+            cwd: None,
+            hostname: None,
+            scope: "personal".to_string(),
+            created_at: Utc::now(),
+            last_used_at: None,
+            use_count: 0,
+            is_new: false,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("build", "build"), 0);
+        assert_eq!(levenshtein("buidl", "build"), 2);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_did_you_mean_finds_close_typo() {
+        let commands = vec![
+            command(1, "rust", "build"),
+            command(2, "rust", "test"),
+            command(3, "node", "deploy"),
+        ];
+        let suggestions = did_you_mean("buidl", &commands, 3);
+        assert_eq!(suggestions.first().unwrap().command.name, "build");
+    }
+
+    #[test]
+    fn test_did_you_mean_excludes_distant_candidates() {
+        let commands = vec![command(1, "node", "deploy")];
+        let suggestions = did_you_mean("buidl", &commands, 3);
+        assert!(suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_did_you_mean_respects_limit() {
+        let commands = vec![
+            command(1, "rust", "build"),
+            command(2, "rust", "builds"),
+            command(3, "rust", "buildr"),
+            command(4, "rust", "buildx"),
+        ];
+        let suggestions = did_you_mean("build", &commands, 2);
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].distance, 0);
+    }
+}
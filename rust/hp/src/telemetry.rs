@@ -0,0 +1,195 @@
+//! Optional OpenTelemetry instrumentation for command execution. Each call to
+//! [`crate::execute_command_with_tracking`] becomes a span (command id, namespace, name, user,
+//! hostname, cwd, resolution method like "id"/"name"/"do", exit code, duration) plus
+//! `hp.executions`/`hp.executions.failures` counters and an `hp.execution.duration_ms` histogram,
+//! exported over OTLP. This is independent of [`crate::api::ExecutionHistoryCreate`] reporting to
+//! the server's `Analytics` endpoint — it lets teams watch `hp` activity in whatever tracing
+//! backend they already run, without scraping that endpoint.
+//!
+//! Off by default: see [`TelemetryConfig`] and [`init`].
+use anyhow::{Context, Result};
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::{global, KeyValue};
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::trace::TracerProvider;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::info_span;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Configures the optional OTLP exporter. Lives under `[telemetry]` in `hiproc.toml`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TelemetryConfig {
+    /// Off by default; also overridable without touching config via `HIPROC_OTEL_ENABLED=1`.
+    #[serde(default)]
+    pub enabled: bool,
+    /// OTLP gRPC endpoint spans and metrics are exported to.
+    #[serde(default = "default_otlp_endpoint")]
+    pub otlp_endpoint: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        TelemetryConfig {
+            enabled: false,
+            otlp_endpoint: default_otlp_endpoint(),
+        }
+    }
+}
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+/// Whether telemetry should be initialized: `HIPROC_OTEL_ENABLED` (set to anything other than
+/// `"0"`/`"false"`) overrides `settings.enabled`, whatever the config file sets it to.
+fn is_enabled(settings: &TelemetryConfig) -> bool {
+    match std::env::var("HIPROC_OTEL_ENABLED") {
+        Ok(value) => !matches!(value.as_str(), "0" | "false"),
+        Err(_) => settings.enabled,
+    }
+}
+
+/// The metric instruments every [`record_execution`] call feeds.
+struct Instruments {
+    executions: Counter<u64>,
+    failures: Counter<u64>,
+    duration_ms: Histogram<f64>,
+}
+
+/// Keeps the OTLP tracer/meter providers alive for the life of the process and flushes them on
+/// drop, so spans and metrics recorded right before exit aren't dropped along with the process.
+pub struct TelemetryGuard {
+    tracer_provider: TracerProvider,
+    meter_provider: SdkMeterProvider,
+    instruments: Instruments,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.tracer_provider.shutdown() {
+            eprintln!("Warning: failed to flush OTLP traces: {e}");
+        }
+        if let Err(e) = self.meter_provider.shutdown() {
+            eprintln!("Warning: failed to flush OTLP metrics: {e}");
+        }
+    }
+}
+
+/// Builds the OTLP trace and metric pipelines and installs a `tracing` layer that forwards spans
+/// to them, if enabled (see [`is_enabled`]). Returns `None` without doing anything else when
+/// disabled, which is the default, so `hp` has zero telemetry overhead out of the box.
+pub fn init(settings: &TelemetryConfig) -> Result<Option<TelemetryGuard>> {
+    if !is_enabled(settings) {
+        return Ok(None);
+    }
+
+    let tracer_provider = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(&settings.otlp_endpoint),
+        )
+        .with_trace_config(opentelemetry_sdk::trace::config().with_resource(
+            opentelemetry_sdk::Resource::new(vec![KeyValue::new("service.name", "hp")]),
+        ))
+        .install_batch(opentelemetry_sdk::runtime::Tokio)
+        .context("Failed to build the OTLP trace pipeline")?;
+    global::set_tracer_provider(tracer_provider.clone());
+
+    let meter_provider = opentelemetry_otlp::new_pipeline()
+        .metrics(opentelemetry_sdk::runtime::Tokio)
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(&settings.otlp_endpoint),
+        )
+        .build()
+        .context("Failed to build the OTLP metrics pipeline")?;
+    global::set_meter_provider(meter_provider.clone());
+
+    let telemetry_layer =
+        tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("hp"));
+    tracing_subscriber::registry()
+        .with(telemetry_layer)
+        .try_init()
+        .context("Failed to install the tracing subscriber")?;
+
+    let instruments = build_instruments(&global::meter("hp"));
+
+    Ok(Some(TelemetryGuard {
+        tracer_provider,
+        meter_provider,
+        instruments,
+    }))
+}
+
+fn build_instruments(meter: &Meter) -> Instruments {
+    Instruments {
+        executions: meter
+            .u64_counter("hp.executions")
+            .with_description("Total hp command executions")
+            .init(),
+        failures: meter
+            .u64_counter("hp.executions.failures")
+            .with_description("hp command executions that exited non-zero")
+            .init(),
+        duration_ms: meter
+            .f64_histogram("hp.execution.duration_ms")
+            .with_description("hp command execution duration in milliseconds")
+            .init(),
+    }
+}
+
+/// Records one execution as a span plus the `executions`/`failures` counters and duration
+/// histogram, when telemetry is enabled (`guard` is `Some`). A no-op otherwise, so callers don't
+/// need to branch on whether telemetry is on.
+#[allow(clippy::too_many_arguments)]
+pub fn record_execution(
+    guard: Option<&TelemetryGuard>,
+    command_id: i32,
+    namespace: &str,
+    name: &str,
+    user: &str,
+    hostname: &str,
+    cwd: &str,
+    resolution_method: &str,
+    exit_code: i32,
+    duration: Duration,
+) {
+    let Some(guard) = guard else { return };
+
+    let span = info_span!(
+        "hp.execute",
+        command_id,
+        namespace,
+        name,
+        user,
+        hostname,
+        cwd,
+        resolution_method,
+        exit_code,
+        duration_ms = duration.as_millis() as u64,
+    );
+    let _enter = span.enter();
+
+    let attributes = [
+        KeyValue::new("command_id", command_id as i64),
+        KeyValue::new("namespace", namespace.to_string()),
+        KeyValue::new("name", name.to_string()),
+        KeyValue::new("user", user.to_string()),
+        KeyValue::new("hostname", hostname.to_string()),
+        KeyValue::new("resolution_method", resolution_method.to_string()),
+    ];
+
+    guard.instruments.executions.add(1, &attributes);
+    if exit_code != 0 {
+        guard.instruments.failures.add(1, &attributes);
+    }
+    guard
+        .instruments
+        .duration_ms
+        .record(duration.as_secs_f64() * 1000.0, &attributes);
+}
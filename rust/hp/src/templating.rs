@@ -1,6 +1,29 @@
 //! Handles the parsing and substitution of runtime arguments into command templates.
 use anyhow::{bail, Result};
-use regex::Regex;
+use regex::{Captures, Regex};
+use std::cell::RefCell;
+use std::env;
+
+/// The grammar for `{{...}}` placeholders that `resolve_arguments` scans for.
+/// [`crate::secrets::resolve_secrets_with`] does not use this regex: it scans the result a second
+/// time with its own regex, which matches only the `{{secret:NAME}}` and `\{{secret:NAME}}` forms:
+///
+/// - `{{NAME}}` — a required argument; resolution fails if it's not supplied.
+/// - `{{NAME:default}}` — an argument that falls back to `default` (with `$VAR` expansion)
+///   when not supplied. `{{NAME:=default}}` (a leading `=` right after the colon) is also
+///   accepted as an alias for the same thing, for compatibility with commands saved before the
+///   colon-only syntax — the leading `=` is stripped before the default is used.
+/// - `{{secret:NAME}}` — forces resolution through the secrets module; left untouched here.
+/// - `\{{...}}` — an escaped literal `{{...}}`, emitted verbatim with the backslash stripped.
+/// - `\{{secret:NAME}}` — an escaped secret placeholder is left completely untouched, backslash
+///   included, so [`crate::secrets::resolve_secrets_with`] (which runs after this function) is
+///   the one that strips the backslash and emits the literal `{{secret:NAME}}` unresolved.
+///   Unescaping it here instead would hand `resolve_secrets_with` a bare `{{secret:NAME}}` and
+///   it would resolve/prompt for the real secret — exactly backwards from what escaping asked for.
+fn placeholder_regex() -> Regex {
+    Regex::new(r"(\\\{\{secret:[a-zA-Z_][a-zA-Z0-9_]*\}\})|\\(\{\{[^}]*\}\})|\{\{secret:([a-zA-Z_][a-zA-Z0-9_]*)\}\}|\{\{([a-zA-Z_][a-zA-Z0-9_]*)(?::([^}]*))?\}\}")
+        .expect("valid regex")
+}
 
 /// A structure to hold the result of parsing user-provided arguments.
 pub struct ParsedArgs {
@@ -27,24 +50,65 @@ fn parse_arguments(raw_args: &[String]) -> ParsedArgs {
     ParsedArgs { named_args, passthrough_args }
 }
 
+/// Expands `$VAR`-style environment variable references within a placeholder's default value.
+/// References to unset variables are left as the empty string.
+fn expand_env_vars(text: &str) -> String {
+    let re = Regex::new(r"\$([a-zA-Z_][a-zA-Z0-9_]*)").expect("valid regex");
+    re.replace_all(text, |caps: &Captures| env::var(&caps[1]).unwrap_or_default())
+        .into_owned()
+}
+
 /// Resolves a command string template with user-provided arguments.
 ///
-/// 1. Substitutes `{{PLACEHOLDER}}` with named arguments (`KEY:VALUE`).
-/// 2. Appends any remaining passthrough arguments to the end.
-/// 3. Fails if any placeholders are left unresolved.
+/// Scans the template once against the shared [`placeholder_regex`] grammar:
+/// 1. `\{{secret:NAME}}` is left fully untouched, backslash included, for `resolve_secrets` to
+///    unescape (see [`placeholder_regex`]'s doc comment for why unescaping it here is wrong).
+/// 2. `{{NAME:default}}` (or the legacy `{{NAME:=default}}`) substitutes the named argument if
+///    provided, otherwise the declared default (expanding `$VAR` references in the default
+///    against the process environment).
+/// 3. `{{NAME}}` substitutes the named argument (`KEY:VALUE`), failing if none was supplied.
+/// 4. `{{secret:NAME}}` is left untouched for `resolve_secrets` to resolve afterward.
+/// 5. `\{{...}}` (anything other than an escaped secret) is unescaped to a literal `{{...}}`.
+/// 6. Any remaining passthrough arguments are appended to the end.
 pub fn resolve_arguments(command_template: &str, raw_args: &[String]) -> Result<String> {
     let args = parse_arguments(raw_args);
-    let mut resolved_command = command_template.to_string();
+    let missing = RefCell::new(Vec::new());
 
-    // Substitute named arguments
-    for (key, value) in args.named_args {
-        resolved_command = resolved_command.replace(&format!("{{{{{}}}}}", key), &value);
-    }
+    let mut resolved_command = placeholder_regex()
+        .replace_all(command_template, |caps: &Captures| {
+            if let Some(escaped_secret) = caps.get(1) {
+                return escaped_secret.as_str().to_string();
+            }
+            if let Some(escaped) = caps.get(2) {
+                return escaped.as_str().to_string();
+            }
+            if caps.get(3).is_some() {
+                // `{{secret:NAME}}` — leave untouched for `resolve_secrets`.
+                return caps[0].to_string();
+            }
+
+            let name = &caps[4];
+            if let Some(value) = args.named_args.get(name) {
+                return value.clone();
+            }
+            match caps.get(5) {
+                Some(default) => {
+                    // Accept the legacy `{{NAME:=default}}` syntax by stripping a leading `=`
+                    // right after the colon, so commands saved under the old grammar still
+                    // resolve to the same default instead of a literal `=`-prefixed string.
+                    let default = default.as_str().strip_prefix('=').unwrap_or(default.as_str());
+                    expand_env_vars(default)
+                }
+                None => {
+                    missing.borrow_mut().push(caps[0].to_string());
+                    caps[0].to_string()
+                }
+            }
+        })
+        .into_owned();
 
-    // Check for any remaining, unresolved placeholders
-    let re = Regex::new(r"\{\{([a-zA-Z_][a-zA-Z0-9_]*)\}\}")?;
-    if let Some(unresolved) = re.find(&resolved_command) {
-        bail!("The following placeholder was not provided: {}", unresolved.as_str());
+    if let Some(unresolved) = missing.borrow().first() {
+        bail!("The following placeholder was not provided: {}", unresolved);
     }
 
     // Append passthrough arguments
@@ -1,6 +1,6 @@
 //! Unit tests for the templating engine.
 #![cfg(test)]
-use super::templating;
+use super::{secrets, templating};
 
 #[test]
 fn test_resolve_no_args() {
@@ -41,6 +41,52 @@ fn test_resolve_mixed_args() {
     );
 }
 
+#[test]
+fn test_resolve_default_used_when_not_provided() {
+    let template = "docker run -p {{PORT:8080}}:80 my-image";
+    let args = vec![];
+    let result = templating::resolve_arguments(template, &args).unwrap();
+    assert_eq!(result, "docker run -p 8080:80 my-image");
+}
+
+#[test]
+fn test_resolve_named_arg_overrides_default() {
+    let template = "docker run -p {{PORT:8080}}:80 my-image";
+    let args = vec!["PORT:9090".to_string()];
+    let result = templating::resolve_arguments(template, &args).unwrap();
+    assert_eq!(result, "docker run -p 9090:80 my-image");
+}
+
+#[test]
+fn test_resolve_default_expands_env_var() {
+    std::env::set_var("HP_TEST_HOSTNAME", "myhost.example.com");
+    let template = "ssh {{HOST:$HP_TEST_HOSTNAME}}";
+    let args = vec![];
+    let result = templating::resolve_arguments(template, &args).unwrap();
+    assert_eq!(result, "ssh myhost.example.com");
+    std::env::remove_var("HP_TEST_HOSTNAME");
+}
+
+#[test]
+fn test_resolve_legacy_default_syntax_still_works() {
+    // `{{NAME:=default}}` is the syntax this repo shipped before the colon-only grammar; it must
+    // keep resolving to the bare default instead of leaking the `=` into the command.
+    let template = "docker run -p {{PORT:=8080}}:80 my-image";
+    let args = vec![];
+    let result = templating::resolve_arguments(template, &args).unwrap();
+    assert_eq!(result, "docker run -p 8080:80 my-image");
+}
+
+#[test]
+fn test_resolve_legacy_default_syntax_expands_env_var() {
+    std::env::set_var("HP_TEST_HOSTNAME", "myhost.example.com");
+    let template = "ssh {{HOST:=$HP_TEST_HOSTNAME}}";
+    let args = vec![];
+    let result = templating::resolve_arguments(template, &args).unwrap();
+    assert_eq!(result, "ssh myhost.example.com");
+    std::env::remove_var("HP_TEST_HOSTNAME");
+}
+
 #[test]
 fn test_resolve_missing_placeholder_fails() {
     let template = "echo {{MESSAGE}}";
@@ -52,3 +98,74 @@ fn test_resolve_missing_placeholder_fails() {
         "The following placeholder was not provided: {{MESSAGE}}"
     );
 }
+
+#[test]
+fn test_resolve_secret_placeholder_left_untouched() {
+    let template = "curl -H \"Authorization: Bearer {{secret:API_TOKEN}}\" {{URL}}";
+    let args = vec!["URL:https://example.com".to_string()];
+    let result = templating::resolve_arguments(template, &args).unwrap();
+    assert_eq!(
+        result,
+        "curl -H \"Authorization: Bearer {{secret:API_TOKEN}}\" https://example.com"
+    );
+}
+
+#[test]
+fn test_resolve_escaped_braces_are_literal() {
+    let template = r"echo \{{NOT_A_PLACEHOLDER}}";
+    let args = vec![];
+    let result = templating::resolve_arguments(template, &args).unwrap();
+    assert_eq!(result, "echo {{NOT_A_PLACEHOLDER}}");
+}
+
+#[test]
+fn test_resolve_escaped_secret_placeholder_left_untouched_for_secrets_module() {
+    // `resolve_arguments` must not unescape `\{{secret:NAME}}` itself — only
+    // `secrets::resolve_secrets_with` should strip the backslash, and it emits the literal
+    // placeholder rather than resolving a real secret.
+    let template = r"curl -H \{{secret:API_TOKEN}} {{URL}}";
+    let args = vec!["URL:https://example.com".to_string()];
+    let result = templating::resolve_arguments(template, &args).unwrap();
+    assert_eq!(result, r"curl -H \{{secret:API_TOKEN}} https://example.com");
+}
+
+#[test]
+fn test_escaped_secret_placeholder_survives_end_to_end() {
+    // Run the full pipeline every call site uses: `resolve_arguments` then
+    // `secrets::resolve_secrets_with`. An escaped secret placeholder must come out as the literal
+    // `{{secret:NAME}}` text, never resolved against a provider (env here would otherwise supply
+    // a real value and prove the escape didn't hold).
+    std::env::set_var("HP_TEST_ESCAPED_SECRET", "leaked-value");
+    let template = r"curl -H \{{secret:HP_TEST_ESCAPED_SECRET}} {{URL}}";
+    let args = vec!["URL:https://example.com".to_string()];
+
+    let templated = templating::resolve_arguments(template, &args).unwrap();
+    let settings = secrets::SecretProviderSettings {
+        default: vec![secrets::SecretProviderSpec::Env],
+        namespaces: Default::default(),
+    };
+    let providers = secrets::providers_for_namespace(&settings, "test");
+    let result = secrets::resolve_secrets_with(&providers, &templated).unwrap();
+
+    std::env::remove_var("HP_TEST_ESCAPED_SECRET");
+    assert_eq!(
+        result,
+        "curl -H {{secret:HP_TEST_ESCAPED_SECRET}} https://example.com"
+    );
+}
+
+#[test]
+fn test_resolve_mixes_default_named_arg_secret_and_passthrough() {
+    let template =
+        "curl -H \"Authorization: Bearer {{secret:API_TOKEN}}\" {{HOST}}:{{PORT:8080}}";
+    let args = vec![
+        "HOST:example.com".to_string(),
+        "--verbose".to_string(),
+        "-k".to_string(),
+    ];
+    let result = templating::resolve_arguments(template, &args).unwrap();
+    assert_eq!(
+        result,
+        "curl -H \"Authorization: Bearer {{secret:API_TOKEN}}\" example.com:8080 --verbose -k"
+    );
+}
@@ -0,0 +1,107 @@
+//! Integration tests for `ApiClient::with_tls` against a locally generated self-signed
+//! certificate, mirroring the CA/mTLS pattern used elsewhere in this codebase's TLS work: a
+//! freshly generated cert pair, a server presenting it, and an agent that must be told to trust
+//! it explicitly.
+#![cfg(test)]
+use super::api::{ApiClient, TlsConfig};
+use rcgen::{CertificateParams, KeyPair};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+/// A self-signed cert pair plus the PEM bytes needed to configure both the server and the
+/// client's trusted CA.
+struct SelfSignedCert {
+    cert_pem: Vec<u8>,
+    key_pem: Vec<u8>,
+}
+
+fn generate_self_signed_cert() -> SelfSignedCert {
+    let key_pair = KeyPair::generate().expect("generate key pair");
+    let params = CertificateParams::new(vec!["localhost".to_string()]).expect("cert params");
+    let cert = params.self_signed(&key_pair).expect("self-sign cert");
+    SelfSignedCert {
+        cert_pem: cert.pem().into_bytes(),
+        key_pem: key_pair.serialize_pem().into_bytes(),
+    }
+}
+
+/// Starts a minimal single-shot TLS server on an ephemeral port that replies to any request with
+/// a fixed JSON array, just enough to exercise `ApiClient::get_namespaces`. Returns the bound
+/// address; the server task runs for exactly one connection and then exits.
+async fn spawn_tls_echo_server(cert: &SelfSignedCert) -> SocketAddr {
+    let certs: Vec<CertificateDer<'static>> =
+        rustls_pemfile::certs(&mut cert.cert_pem.as_slice())
+            .collect::<Result<_, _>>()
+            .expect("parse cert PEM");
+    let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut cert.key_pem.as_slice())
+        .expect("parse key PEM")
+        .expect("key PEM contained no private key");
+
+    let server_config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .expect("build server TLS config");
+    let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let addr = listener.local_addr().expect("local addr");
+
+    tokio::spawn(async move {
+        let Ok((stream, _)) = listener.accept().await else {
+            return;
+        };
+        let Ok(mut tls_stream) = acceptor.accept(stream).await else {
+            return;
+        };
+
+        let mut buf = [0u8; 1024];
+        let _ = tls_stream.read(&mut buf).await;
+
+        let body = b"[\"personal\"]";
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        let _ = tls_stream.write_all(response.as_bytes()).await;
+        let _ = tls_stream.write_all(body).await;
+        let _ = tls_stream.shutdown().await;
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn test_connection_fails_without_trusted_ca() {
+    let cert = generate_self_signed_cert();
+    let addr = spawn_tls_echo_server(&cert).await;
+
+    let client = ApiClient::with_tls(
+        format!("https://localhost:{}", addr.port()),
+        TlsConfig::new(),
+    )
+    .expect("building the client itself should not fail");
+
+    let result = client.get_namespaces().await;
+    assert!(result.is_err(), "expected a handshake failure without the CA configured");
+}
+
+#[tokio::test]
+async fn test_connection_succeeds_with_trusted_ca() {
+    let cert = generate_self_signed_cert();
+    let addr = spawn_tls_echo_server(&cert).await;
+
+    let client = ApiClient::with_tls(
+        format!("https://localhost:{}", addr.port()),
+        TlsConfig::new().with_ca_cert_pem(cert.cert_pem.clone()),
+    )
+    .expect("failed to build TLS-configured client");
+
+    let result = client.get_namespaces().await;
+    assert!(result.is_ok(), "expected the request to succeed once the CA is trusted: {:?}", result.err());
+    assert_eq!(result.unwrap(), vec!["personal".to_string()]);
+}
@@ -0,0 +1,254 @@
+//! An encrypted local secret vault, so `{{secret:NAME}}` resolution doesn't have to re-prompt
+//! for the same value on every invocation.
+//!
+//! Secrets are persisted under the config directory (see [`crate::config::config_dir`]) in a
+//! `secrets.vault` file, mode `0600`. The master key is derived from a user passphrase with
+//! Argon2id and never touches disk; each secret is encrypted individually with
+//! ChaCha20-Poly1305 under a random per-entry nonce. The file holds the secret names, each
+//! entry's nonce and ciphertext, and the Argon2 salt/parameters needed to re-derive the key;
+//! secret values are never written in plaintext.
+use crate::config::config_dir;
+use anyhow::{bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use zeroize::Zeroizing;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// Argon2id cost parameters for deriving the vault's master key. Tuned to cost roughly 100ms on
+/// commodity hardware, which is unnoticeable for an interactive "unseal once per run" prompt.
+const ARGON2_M_COST_KIB: u32 = 19 * 1024;
+const ARGON2_T_COST: u32 = 2;
+const ARGON2_P_COST: u32 = 1;
+
+/// A reserved entry name (can never collide with a `{{secret:NAME}}` secret name, which must
+/// match `[a-zA-Z_][a-zA-Z0-9_]*`) whose decrypted value must equal [`CHECK_ENTRY_VALUE`].
+/// Lets `unlock` fail closed on a wrong passphrase immediately, rather than only on the next
+/// secret lookup.
+const CHECK_ENTRY_NAME: &str = "\u{0}vault-check";
+const CHECK_ENTRY_VALUE: &str = "hiproc-vault-ok";
+
+/// An error a caller can match on via `anyhow::Error::downcast_ref` to distinguish "wrong
+/// passphrase" from other I/O or parsing failures.
+#[derive(Debug)]
+pub enum VaultError {
+    /// The supplied passphrase did not unseal the vault: either it's wrong, or the vault file's
+    /// contents were tampered with and failed their authentication tag check.
+    AuthenticationFailed,
+}
+
+impl std::fmt::Display for VaultError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VaultError::AuthenticationFailed => write!(
+                f,
+                "Could not unseal the secret vault: wrong passphrase, or the vault file is corrupted"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VaultError {}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct VaultEntry {
+    /// Hex-encoded 96-bit nonce, unique per entry.
+    nonce: String,
+    /// Hex-encoded ChaCha20-Poly1305 ciphertext (includes the authentication tag).
+    ciphertext: String,
+}
+
+/// On-disk vault format: the Argon2id salt/parameters used to derive the master key, plus the
+/// map of secret name -> encrypted entry.
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    #[serde(default)]
+    entries: HashMap<String, VaultEntry>,
+}
+
+/// An unsealed secret vault. Holds the Argon2id-derived master key, which is zeroized on drop,
+/// and decrypts/encrypts individual entries on demand.
+pub struct SecretVault {
+    path: PathBuf,
+    key: Zeroizing<[u8; KEY_LEN]>,
+    file: VaultFile,
+}
+
+impl SecretVault {
+    fn path() -> Result<PathBuf> {
+        config_dir()
+            .map(|dir| dir.join("secrets.vault"))
+            .context("Could not determine the config directory for the secret vault")
+    }
+
+    /// Whether a vault file already exists on disk.
+    pub fn exists() -> Result<bool> {
+        Ok(Self::path()?.is_file())
+    }
+
+    /// Creates a brand-new, empty vault sealed with `passphrase` and writes it to disk.
+    pub fn create(passphrase: &str) -> Result<Self> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .context("Failed to create config directory for secret vault")?;
+        }
+
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let key = derive_key(
+            passphrase,
+            &salt,
+            ARGON2_M_COST_KIB,
+            ARGON2_T_COST,
+            ARGON2_P_COST,
+        )?;
+
+        let mut vault = SecretVault {
+            path,
+            key,
+            file: VaultFile {
+                salt: encode_hex(&salt),
+                m_cost: ARGON2_M_COST_KIB,
+                t_cost: ARGON2_T_COST,
+                p_cost: ARGON2_P_COST,
+                entries: HashMap::new(),
+            },
+        };
+        let check_entry = encrypt(&vault.key, CHECK_ENTRY_VALUE)?;
+        vault
+            .file
+            .entries
+            .insert(CHECK_ENTRY_NAME.to_string(), check_entry);
+        vault.save()?;
+        Ok(vault)
+    }
+
+    /// Unseals the existing vault file with `passphrase`. Fails closed (returns
+    /// [`VaultError::AuthenticationFailed`]) if the passphrase is wrong or the file was
+    /// tampered with, rather than returning a vault that will only fail on first use.
+    pub fn unlock(passphrase: &str) -> Result<Self> {
+        let path = Self::path()?;
+        let data = fs::read_to_string(&path).context("Failed to read secret vault")?;
+        let file: VaultFile =
+            serde_json::from_str(&data).context("Secret vault file is corrupt")?;
+        let salt = decode_hex(&file.salt)?;
+        let key = derive_key(passphrase, &salt, file.m_cost, file.t_cost, file.p_cost)?;
+
+        let vault = SecretVault { path, key, file };
+        if let Some(check_entry) = vault.file.entries.get(CHECK_ENTRY_NAME) {
+            if decrypt(&vault.key, check_entry)?.as_str() != CHECK_ENTRY_VALUE {
+                bail!(VaultError::AuthenticationFailed);
+            }
+        }
+        Ok(vault)
+    }
+
+    /// Returns the decrypted value of `name`, or `None` if it isn't in the vault. Fails closed
+    /// if the entry's authentication tag doesn't verify. Wrapped in [`Zeroizing`] so the
+    /// plaintext is scrubbed from memory as soon as the caller drops it, same as the master key.
+    pub fn get(&self, name: &str) -> Result<Option<Zeroizing<String>>> {
+        match self.file.entries.get(name) {
+            Some(entry) => decrypt(&self.key, entry).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Encrypts `value` under a fresh random nonce, stores it as `name`, and persists the vault.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        let entry = encrypt(&self.key, value)?;
+        self.file.entries.insert(name.to_string(), entry);
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(&self.file).context("Failed to serialize secret vault")?;
+        fs::write(&self.path, json).context("Failed to write secret vault")?;
+        let mut perms = fs::metadata(&self.path)
+            .context("Failed to stat secret vault after writing it")?
+            .permissions();
+        perms.set_mode(0o600);
+        fs::set_permissions(&self.path, perms)
+            .context("Failed to restrict secret vault permissions")?;
+        Ok(())
+    }
+}
+
+/// Derives a 256-bit key from `passphrase` and `salt` with Argon2id.
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+) -> Result<Zeroizing<[u8; KEY_LEN]>> {
+    let params = Params::new(m_cost, t_cost, p_cost, Some(KEY_LEN))
+        .map_err(|e| anyhow::anyhow!("Invalid Argon2 parameters: {e}"))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut key = [0u8; KEY_LEN];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Argon2id key derivation failed: {e}"))?;
+    Ok(Zeroizing::new(key))
+}
+
+/// Encrypts `plaintext` under a fresh random 96-bit nonce.
+fn encrypt(key: &[u8; KEY_LEN], plaintext: &str) -> Result<VaultEntry> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), plaintext.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt secret"))?;
+
+    Ok(VaultEntry {
+        nonce: encode_hex(&nonce_bytes),
+        ciphertext: encode_hex(&ciphertext),
+    })
+}
+
+/// Decrypts `entry`, failing closed (as [`VaultError::AuthenticationFailed`]) if the
+/// authentication tag doesn't verify against `key`. The plaintext is zeroized on drop.
+fn decrypt(key: &[u8; KEY_LEN], entry: &VaultEntry) -> Result<Zeroizing<String>> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce_bytes = decode_hex(&entry.nonce)?;
+    let ciphertext = decode_hex(&entry.ciphertext)?;
+
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce_bytes), ciphertext.as_ref())
+        .map_err(|_| anyhow::Error::new(VaultError::AuthenticationFailed))?;
+    String::from_utf8(plaintext)
+        .map(Zeroizing::new)
+        .context("Decrypted vault entry was not valid UTF-8")
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        bail!("Invalid hex string in secret vault");
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).context("Invalid hex byte in secret vault"))
+        .collect()
+}